@@ -1,7 +1,26 @@
-// Aqui são os Statements/Comandos, que alteram o fluxo de execução do código e afins
+use crate::span::Span;
+
+// Aqui são os Statements/Comandos, que alteram o fluxo de execução do código e afins. Cada um
+// guarda o trecho do código de onde saiu, para as mensagens de erro
 #[derive(Debug, Clone)]
-pub enum Stmt {
-    VarDecl { name: String, value: Expr },
+pub struct Stmt {
+    pub kind: StmtKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum StmtKind {
+    // Declaração de variável: int x = 1; ou struct Ponto p = {1, 2};. Sem inicializador a variável
+    // começa zerada
+    VarDecl { name: String, ty: Type, init: Option<Initializer> },
+    // Declaração de array: int v[10]; ou int v[] = {1, 2, 3}; ou int m[2][3];. Só o tamanho da
+    // primeira dimensão pode ser omitido, quando há inicializador
+    ArrayDecl {
+        name: String,
+        ty: Type,
+        dims: Vec<Option<Expr>>,
+        init: Option<Initializer>,
+    },
     Return(Expr),
     ExprStmt(Expr),
     If {
@@ -13,19 +32,59 @@ pub enum Stmt {
         condition: Expr,
         body: Vec<Stmt>
     },
+    // do { ... } while (condição);: o corpo é executado antes de a condição ser testada
+    DoWhile {
+        body: Vec<Stmt>,
+        condition: Expr,
+    },
     For {
         init: Option<Box<Stmt>>,
         condition: Option<Expr>,
         update: Option<Expr>,
         body: Vec<Stmt>
     },
+    // switch (valor) { case 1: ... default: ... }. A execução começa no case certo e segue pelos
+    // seguintes até um break, como em C
+    Switch {
+        subject: Expr,
+        cases: Vec<SwitchCase>,
+    },
+    // break sai do laço ou switch mais próximo; continue pula para a próxima volta do laço
+    Break,
+    Continue,
+}
+
+// Um case do switch: o valor (já calculado pelo parser, None no default) e os statements até o
+// próximo case
+#[derive(Debug, Clone)]
+pub struct SwitchCase {
+    pub label: Option<i64>,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+// O inicializador de uma variável ou array: um valor, ou uma lista entre chaves que pode ter outras
+// listas dentro (como em {{1, 2}, {3, 4}}), com o trecho do código da lista
+#[derive(Debug, Clone)]
+pub enum Initializer {
+    Value(Expr),
+    List(Vec<Initializer>, Span),
 }
 
 // Aqui são as expressões, que podem ser avaliadas pra produzir um valor
 #[derive(Debug, Clone)]
-pub enum Expr {
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
     Number(i64),
+    Float(f64),
     Bool(bool),
+    Str(String),
+    Char(char),
     Var(String),
     Binary {
         op: BinOp,
@@ -41,27 +100,201 @@ pub enum Expr {
         op: UnaryOp,
         expr: Box<Expr>,
     },
+    // condição ? valor : outro valor, em que só o valor escolhido é avaliado
+    Conditional {
+        condition: Box<Expr>,
+        then_expr: Box<Expr>,
+        else_expr: Box<Expr>,
+    },
+    // Conversão explícita, como em (int)x
+    Cast {
+        ty: Type,
+        expr: Box<Expr>,
+    },
     Call {
         name: String,
         args: Vec<Expr>,
     },
+    // Acesso a um elemento de array, como em v[i]
+    Index {
+        array: Box<Expr>,
+        index: Box<Expr>,
+    },
+    // Acesso a um campo de struct, como em p.x
+    Field {
+        object: Box<Expr>,
+        field: String,
+    },
+    // Nas atribuições e atualizações o alvo é uma variável, um elemento de array (v[i]) ou um campo
+    // de struct (p.x)
+    Assign {
+        target: Box<Expr>,
+        value: Box<Expr>,
+    },
+    // Atribuição composta, como em x += 2 (equivale a x = x + 2)
+    CompoundAssign {
+        target: Box<Expr>,
+        op: BinOp,
+        value: Box<Expr>,
+    },
+    // ++x e --x: atualiza o alvo e produz o valor novo
+    PrefixUpdate {
+        op: UpdateOp,
+        target: Box<Expr>,
+    },
+    // x++ e x--: atualiza o alvo e produz o valor antigo
+    PostfixUpdate {
+        op: UpdateOp,
+        target: Box<Expr>,
+    },
+}
+
+// O programa inteiro: a sequência de itens declarados no escopo do arquivo, na ordem do código
+#[derive(Debug, Clone, Default)]
+pub struct Program {
+    pub items: Vec<Item>,
+}
+
+// Os itens que podem aparecer no escopo do arquivo: funções, variáveis globais e definições de
+// struct e enum
+#[derive(Debug, Clone)]
+pub enum Item {
+    Function(Function),
+    Global(Stmt),
+    Struct(StructDef),
+    Enum(EnumDef),
+}
+
+impl Program {
+    // Todas as funções do programa, incluindo a main
+    pub fn functions(&self) -> impl Iterator<Item = &Function> {
+        self.items.iter().filter_map(|item| match item {
+            Item::Function(func) => Some(func),
+            _ => None,
+        })
+    }
+
+    // Procura uma função pelo nome
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions().find(|func| func.name == name)
+    }
+}
+
+// O que pode ser digitado no REPL: definições de função, struct e enum, statements e expressões
+// soltas (sem ';' no final), cujo valor é mostrado de volta
+#[derive(Debug, Clone)]
+pub enum ReplInput {
+    Function(Function),
+    Struct(StructDef),
+    Enum(EnumDef),
+    Stmt(Stmt),
+    Expr(Expr),
 }
 
 // Representa as funções, com nomes, parâMetros e um corpo de código a ser executado
 #[derive(Debug, Clone)]
 pub struct Function {
     pub name: String,
-    pub params: Vec<String>,
+    pub return_type: Type,
+    pub params: Vec<Param>,
     pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+// Um parâMetro de função, com o tipo declarado. Um parâMetro array (int v[] ou int m[][4]) recebe
+// o array de quem chamou por referência, como em C
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub ty: Type,
+    pub is_array: bool,
 }
 
-// Operações aritméticas e de comparação
+// Uma definição de struct, como struct Ponto { int x; int y; };. Os campos ficam na ordem em que
+// foram declarados, que é a ordem usada pelos inicializadores {1, 2}
+#[derive(Debug, Clone)]
+pub struct StructDef {
+    pub name: String,
+    pub fields: Vec<Field>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+}
+
+// Uma definição de enum, como enum Cor { VERMELHO, VERDE = 5, AZUL };. O parser já calcula o valor
+// de cada constante: o valor escrito depois do '=' ou o da anterior mais um (começando do 0)
+#[derive(Debug, Clone)]
+pub struct EnumDef {
+    pub name: String,
+    pub constants: Vec<(String, i64)>,
+    pub span: Span,
+}
+
+// Os tipos que podem ser escritos no código. float e double são o mesmo tipo, struct Nome é o tipo
+// de uma struct definida no programa e enum Nome é o mesmo que int, como em C
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Int,
+    Bool,
+    String,
+    Double,
+    Char,
+    Struct(String),
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Type::Int => "int",
+            Type::Bool => "bool",
+            Type::String => "string",
+            Type::Double => "double",
+            Type::Char => "char",
+            Type::Struct(name) => return write!(f, "struct {}", name),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl Stmt {
+    pub fn new(kind: StmtKind, span: Span) -> Self {
+        Stmt { kind, span }
+    }
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span }
+    }
+
+    // Se a expressão pode receber uma atribuição: uma variável, um elemento de array ou um campo de
+    // algo que também pode receber atribuição (p.x e v[i].x, mas não f().x)
+    pub fn is_assignable(&self) -> bool {
+        match &self.kind {
+            ExprKind::Var(_) | ExprKind::Index { .. } => true,
+            ExprKind::Field { object, .. } => object.is_assignable(),
+            _ => false,
+        }
+    }
+}
+
+// Operações aritméticas, bit a bit e de comparação
 #[derive(Debug, Clone, Copy)]
 pub enum BinOp {
     Add,
     Sub,
     Mul,
     Div,
+    Mod,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
     Equal,
     NotEqual,
     Greater,
@@ -70,6 +303,31 @@ pub enum BinOp {
     LessEqual,
 }
 
+// O operador como aparece no código, usado nas mensagens de erro
+impl std::fmt::Display for BinOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Mod => "%",
+            BinOp::BitAnd => "&",
+            BinOp::BitOr => "|",
+            BinOp::BitXor => "^",
+            BinOp::ShiftLeft => "<<",
+            BinOp::ShiftRight => ">>",
+            BinOp::Equal => "==",
+            BinOp::NotEqual => "!=",
+            BinOp::Greater => ">",
+            BinOp::GreaterEqual => ">=",
+            BinOp::Less => "<",
+            BinOp::LessEqual => "<=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
 // Operações lógicas (AND, OR)
 #[derive(Debug, Clone, Copy)]
 pub enum LogicalOp {
@@ -77,9 +335,17 @@ pub enum LogicalOp {
     Or,
 }
 
-// Operações unárias (NOT, negação)
+// Operações unárias (NOT, negação e o NOT bit a bit ~)
 #[derive(Debug, Clone, Copy)]
 pub enum UnaryOp {
     Not,
     Minus,
+    BitNot,
+}
+
+// Operações de incremento e decremento (++, --)
+#[derive(Debug, Clone, Copy)]
+pub enum UpdateOp {
+    Increment,
+    Decrement,
 }
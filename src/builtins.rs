@@ -0,0 +1,256 @@
+// As funções embutidas da linguagem, que existem sem precisar ser definidas no programa. O
+// interpretador procura aqui antes de procurar nas funções do usuário
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+use crate::interpreter::{RuntimeError, Value};
+use crate::span::Span;
+
+// Uma função embutida recebe os argumentos já avaliados e a saída onde pode escrever
+pub type Builtin = fn(&[Value], &mut dyn Write, Span) -> Result<Value, RuntimeError>;
+
+// Os nomes de todas as funções embutidas, usados também nas sugestões de "você quis dizer"
+pub const NAMES: &[&str] = &["print", "println", "printf", "len", "substr", "char_at", "to_int", "to_string"];
+
+pub fn lookup(name: &str) -> Option<Builtin> {
+    match name {
+        "print" => Some(print),
+        "println" => Some(println),
+        "printf" => Some(printf),
+        "len" => Some(len),
+        "substr" => Some(substr),
+        "char_at" => Some(char_at),
+        "to_int" => Some(to_int),
+        "to_string" => Some(to_string),
+        _ => None,
+    }
+}
+
+// Escreve os argumentos separados por espaço, sem quebra de linha no final
+fn print(args: &[Value], out: &mut dyn Write, span: Span) -> Result<Value, RuntimeError> {
+    let text = join(args);
+    write_out("print", out, &text, span)?;
+    Ok(Value::Void)
+}
+
+// Igual ao print, mas termina com uma quebra de linha
+fn println(args: &[Value], out: &mut dyn Write, span: Span) -> Result<Value, RuntimeError> {
+    let text = join(args) + "\n";
+    write_out("println", out, &text, span)?;
+    Ok(Value::Void)
+}
+
+// printf("formato", args...): %d para int, %f para double (com 6 casas, ou %.2f para 2), %c para
+// char, %b para bool, %s para texto e %% para o próprio %. Devolve quantos bytes foram escritos,
+// como em C
+fn printf(args: &[Value], out: &mut dyn Write, span: Span) -> Result<Value, RuntimeError> {
+    let error = |message: String| RuntimeError::BuiltinError("printf".to_string(), message, span);
+
+    let (format, mut rest) = match args.split_first() {
+        Some((Value::Str(format), rest)) => (format, rest.iter()),
+        Some((other, _)) => {
+            return Err(error(format!("o formato precisa ser um texto, mas recebeu {}", other)));
+        }
+        None => return Err(error("falta o texto de formato".to_string())),
+    };
+
+    let mut text = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            text.push(c);
+            continue;
+        }
+
+        let missing_spec = || error("o formato termina com um '%' incompleto".to_string());
+        let mut spec = chars.next().ok_or_else(missing_spec)?;
+        if spec == '%' {
+            text.push('%');
+            continue;
+        }
+
+        let mut precision = 6;
+        if spec == '.' {
+            let mut digits = String::new();
+            while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                digits.push(d);
+            }
+            spec = chars.next().ok_or_else(missing_spec)?;
+            precision = match digits.parse::<usize>() {
+                Ok(precision) if spec == 'f' => precision,
+                _ => return Err(error(format!("precisão inválida em '%.{}{}'", digits, spec))),
+            };
+        }
+
+        let arg = rest
+            .next()
+            .ok_or_else(|| error(format!("falta um argumento para o '%{}'", spec)))?;
+        match (spec, arg) {
+            ('d', Value::Number(n)) => text.push_str(&n.to_string()),
+            ('d', Value::Bool(b)) => text.push_str(&i64::from(*b).to_string()),
+            ('d', Value::Char(c)) => text.push_str(&(*c as u32).to_string()),
+            ('c', Value::Char(c)) => text.push(*c),
+            ('f', Value::Float(f)) => text.push_str(&format!("{:.*}", precision, f)),
+            ('f', Value::Number(n)) => text.push_str(&format!("{:.*}", precision, *n as f64)),
+            ('b', Value::Bool(b)) => text.push_str(&b.to_string()),
+            ('s', Value::Str(s)) => text.push_str(s),
+            ('d' | 'f' | 'c' | 'b' | 's', other) => {
+                return Err(error(format!("'%{}' não aceita o valor {}", spec, other)));
+            }
+            _ => return Err(error(format!("especificador de formato desconhecido '%{}'", spec))),
+        }
+    }
+
+    if rest.next().is_some() {
+        return Err(error("sobraram argumentos que o formato não usa".to_string()));
+    }
+
+    write_out("printf", out, &text, span)?;
+    Ok(Value::Number(text.len() as i64))
+}
+
+// len(texto) ou len(array): quantos caracteres o texto tem, ou quantos elementos o array tem
+fn len(args: &[Value], _out: &mut dyn Write, span: Span) -> Result<Value, RuntimeError> {
+    expect_count("len", args, 1, span)?;
+    match &args[0] {
+        Value::Array(elements) => Ok(Value::Number(elements.borrow().len() as i64)),
+        other => Ok(Value::Number(expect_str("len", other, span)?.chars().count() as i64)),
+    }
+}
+
+// substr(texto, início, quantidade): o trecho que começa no caractere `início`. Se a quantidade
+// passar do fim, o trecho vai só até o fim do texto
+fn substr(args: &[Value], _out: &mut dyn Write, span: Span) -> Result<Value, RuntimeError> {
+    expect_count("substr", args, 3, span)?;
+    let text = expect_str("substr", &args[0], span)?;
+    let start = expect_int("substr", &args[1], span)?;
+    let count = expect_int("substr", &args[2], span)?;
+
+    let length = text.chars().count() as i64;
+    if start < 0 || start > length {
+        return Err(out_of_range("substr", start, length, span));
+    }
+    if count < 0 {
+        return Err(RuntimeError::BuiltinError(
+            "substr".to_string(),
+            format!("a quantidade de caracteres não pode ser negativa ({})", count),
+            span,
+        ));
+    }
+    Ok(Value::Str(text.chars().skip(start as usize).take(count as usize).collect()))
+}
+
+// char_at(texto, posição): o caractere na posição
+fn char_at(args: &[Value], _out: &mut dyn Write, span: Span) -> Result<Value, RuntimeError> {
+    expect_count("char_at", args, 2, span)?;
+    let text = expect_str("char_at", &args[0], span)?;
+    let index = expect_int("char_at", &args[1], span)?;
+
+    let length = text.chars().count() as i64;
+    if index < 0 || index >= length {
+        return Err(out_of_range("char_at", index, length, span));
+    }
+    Ok(Value::Char(text.chars().nth(index as usize).unwrap()))
+}
+
+// to_int(valor): converte um texto como "42" para int. Também aceita bool, double (truncando) e
+// char (o código do caractere)
+fn to_int(args: &[Value], _out: &mut dyn Write, span: Span) -> Result<Value, RuntimeError> {
+    expect_count("to_int", args, 1, span)?;
+    match &args[0] {
+        Value::Number(n) => Ok(Value::Number(*n)),
+        Value::Float(f) => Ok(Value::Number(*f as i64)),
+        Value::Char(c) => Ok(Value::Number(*c as i64)),
+        Value::Bool(b) => Ok(Value::Number(i64::from(*b))),
+        Value::Str(text) => text.trim().parse::<i64>().map(Value::Number).map_err(|_| {
+            RuntimeError::BuiltinError("to_int".to_string(), format!("\"{}\" não é um número inteiro", text), span)
+        }),
+        other => Err(wrong_type("to_int", "string", other, span)),
+    }
+}
+
+// to_string(valor): o valor como texto, do jeito que o print mostraria
+fn to_string(args: &[Value], _out: &mut dyn Write, span: Span) -> Result<Value, RuntimeError> {
+    expect_count("to_string", args, 1, span)?;
+    Ok(Value::Str(args[0].to_string()))
+}
+
+fn expect_count(name: &str, args: &[Value], expected: usize, span: Span) -> Result<(), RuntimeError> {
+    if args.len() != expected {
+        return Err(RuntimeError::WrongArgumentCount(name.to_string(), expected, args.len(), span));
+    }
+    Ok(())
+}
+
+fn expect_str<'a>(name: &str, value: &'a Value, span: Span) -> Result<&'a str, RuntimeError> {
+    match value {
+        Value::Str(text) => Ok(text),
+        other => Err(wrong_type(name, "string", other, span)),
+    }
+}
+
+fn expect_int(name: &str, value: &Value, span: Span) -> Result<i64, RuntimeError> {
+    match value {
+        Value::Number(n) => Ok(*n),
+        other => Err(wrong_type(name, "int", other, span)),
+    }
+}
+
+fn wrong_type(name: &str, expected: &str, found: &Value, span: Span) -> RuntimeError {
+    RuntimeError::TypeError(
+        format!("'{}' espera {}, mas recebeu {}", name, expected, found.type_name()),
+        span,
+    )
+}
+
+fn out_of_range(name: &str, index: i64, length: i64, span: Span) -> RuntimeError {
+    RuntimeError::BuiltinError(
+        name.to_string(),
+        format!("a posição {} está fora do texto de tamanho {}", index, length),
+        span,
+    )
+}
+
+fn join(args: &[Value]) -> String {
+    args.iter().map(Value::to_string).collect::<Vec<_>>().join(" ")
+}
+
+fn write_out(name: &str, out: &mut dyn Write, text: &str, span: Span) -> Result<(), RuntimeError> {
+    out.write_all(text.as_bytes()).map_err(|e| {
+        RuntimeError::BuiltinError(name.to_string(), format!("não foi possível escrever a saída ({})", e), span)
+    })
+}
+
+// Uma saída em memória que pode ser clonada: o interpretador escreve numa cópia e quem criou lê o
+// que foi escrito pela outra. Serve para capturar a saída do programa nos testes
+#[derive(Debug, Clone, Default)]
+pub struct SharedOutput {
+    buffer: Rc<RefCell<Vec<u8>>>,
+}
+
+impl SharedOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Tudo o que já foi escrito até agora
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.borrow()).into_owned()
+    }
+
+    // O que foi escrito desde a última chamada, esvaziando a saída
+    pub fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.buffer.borrow_mut())
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.borrow_mut().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
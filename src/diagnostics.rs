@@ -0,0 +1,164 @@
+// Aqui os erros do lexer, do parser e do interpretador viram diagnósticos no estilo do rustc:
+// a mensagem, o arquivo com linha:coluna, a linha do código com o trecho sublinhado e, quando
+// der, uma dica de correção
+use crate::interpreter::{Interpreter, RuntimeError};
+use crate::lexer::LexError;
+use crate::parser::ParseError;
+use crate::span::Span;
+
+// Códigos ANSI usados no modo colorido do terminal
+const VERMELHO: &str = "\x1b[1;31m";
+const AZUL: &str = "\x1b[1;34m";
+const CIANO: &str = "\x1b[1;36m";
+const NEGRITO: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            message: message.into(),
+            span,
+            help: None,
+        }
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    // Monta o diagnóstico de um erro de execução, sugerindo um nome parecido quando a função,
+    // a variável ou o campo da struct não existe
+    pub fn from_runtime_error(error: &RuntimeError, interpreter: &Interpreter) -> Self {
+        let message = error.to_string();
+        let message = message.strip_prefix("Erro: ").unwrap_or(&message).to_string();
+        let diagnostic = Diagnostic::new(message, error.span());
+
+        let suggestion = match error {
+            RuntimeError::UndefinedFunction(name, _) => suggest(name, interpreter.function_names()),
+            RuntimeError::UndefinedVariable(name, in_scope, _) => suggest(name, in_scope.iter().map(String::as_str)),
+            RuntimeError::UndefinedField(name, field, _) => suggest(field, interpreter.field_names(name)),
+            _ => None,
+        };
+
+        match suggestion {
+            Some(similar) => diagnostic.with_help(format!("você quis dizer `{}`?", similar)),
+            None => diagnostic,
+        }
+    }
+
+    // Gera o texto do diagnóstico. Com `color` ligado, usa cores ANSI para o terminal; sem ele,
+    // o texto é puro, bom para arquivos de log
+    pub fn render(&self, file_name: &str, source: &str, color: bool) -> String {
+        let paint = |code: &str, text: &str| {
+            if color {
+                format!("{}{}{}", code, text, RESET)
+            } else {
+                text.to_string()
+            }
+        };
+
+        let line_number = self.span.line.max(1);
+        let line_text = source.lines().nth(line_number - 1).unwrap_or("");
+        let gutter = " ".repeat(line_number.to_string().len());
+
+        // o sublinhado vai do início do span até o fim dele ou da linha, o que vier antes
+        let column = self.span.column.max(1);
+        let available = line_text.chars().count().saturating_sub(column - 1);
+        let span_width = source
+            .get(self.span.start..self.span.end)
+            .map_or(0, |text| text.lines().next().unwrap_or("").chars().count());
+        let width = span_width.min(available).max(1);
+        // o recuo do sublinhado copia as tabulações da linha, para que o '^' fique embaixo do trecho
+        // qualquer que seja a largura da tabulação no terminal
+        let padding: String = line_text
+            .chars()
+            .take(column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        let mut out = String::new();
+        out.push_str(&format!("{}{}\n", paint(VERMELHO, "erro"), paint(NEGRITO, &format!(": {}", self.message))));
+        out.push_str(&format!("{}{} {}:{}:{}\n", gutter, paint(AZUL, "-->"), file_name, line_number, column));
+        out.push_str(&format!("{} {}\n", gutter, paint(AZUL, "|")));
+        out.push_str(&format!("{} {} {}\n", paint(AZUL, &line_number.to_string()), paint(AZUL, "|"), line_text));
+        out.push_str(&format!(
+            "{} {} {}{}\n",
+            gutter,
+            paint(AZUL, "|"),
+            padding,
+            paint(VERMELHO, &"^".repeat(width))
+        ));
+
+        if let Some(help) = &self.help {
+            out.push_str(&format!("{} {}\n", gutter, paint(AZUL, "|")));
+            out.push_str(&format!("{} {} {}\n", gutter, paint(CIANO, "= ajuda:"), help));
+        }
+
+        out
+    }
+}
+
+impl From<&LexError> for Diagnostic {
+    fn from(error: &LexError) -> Self {
+        let message = error.to_string();
+        let message = message.strip_prefix("Erro léxico: ").unwrap_or(&message).to_string();
+        Diagnostic::new(format!("erro léxico, {}", message), error.span)
+    }
+}
+
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        let message = error.to_string();
+        let message = message.strip_prefix("Erro de sintaxe: ").unwrap_or(&message).to_string();
+        Diagnostic::new(format!("erro de sintaxe, {}", message), error.span)
+    }
+}
+
+// Distância de edição entre duas palavras: quantas inserções, remoções, trocas de caractere ou
+// inversões de dois caracteres vizinhos (o erro de digitação mais comum) transformam uma na outra
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    d[a.len()][b.len()]
+}
+
+// Procura entre os candidatos o nome mais parecido, desde que a diferença seja pequena perto
+// do tamanho do nome (no máximo um terço dele, e pelo menos 1)
+pub fn suggest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(1);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, candidate)| (*distance, *candidate))
+        .map(|(_, candidate)| candidate)
+}
@@ -1,11 +1,62 @@
 use std::collections::HashMap;
-use crate::ast::{Function};
+use crate::interpreter::Value;
 
-// Aqui será o ambiente de execução do programa, em que variáveis e funções associadas a nomes
-// serão acessadas. Elas estão em hash pra facilitar o acesso e ser mais dinâMica, de maneira geral
-#[derive(Default)]
+// Aqui será o ambiente de execução do programa, em que variáveis associadas a nomes serão
+// acessadas. Ele é uma pilha de escopos: cada bloco empilha um escopo novo e o desempilha ao
+// terminar, então o que foi declarado dentro de um if ou for não vaza para fora dele. Os escopos
+// estão em hash pra facilitar o acesso e ser mais dinâMico, de maneira geral
+#[derive(Debug, Clone)]
 pub struct Environment {
-    pub variables: HashMap<String, i64>,
-    pub functions: HashMap<String, Function>,
+    scopes: Vec<HashMap<String, Value>>,
 }
 
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Environment {
+    // Começa sempre com um escopo, que é o escopo mais externo da função
+    pub fn new() -> Self {
+        Environment {
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        // o escopo mais externo nunca é removido
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    // Declara a variável no escopo mais interno. Retorna false se ela já existia nesse mesmo
+    // escopo; em escopos internos o sombreamento é permitido
+    pub fn declare(&mut self, name: &str, value: Value) -> bool {
+        let scope = self.scopes.last_mut().expect("sempre há ao menos um escopo");
+        if scope.contains_key(name) {
+            return false;
+        }
+        scope.insert(name.to_string(), value);
+        true
+    }
+
+    // Procura a variável do escopo mais interno para o mais externo
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    // Os nomes de todas as variáveis visíveis, em todos os escopos
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.scopes.iter().flat_map(|scope| scope.keys()).map(String::as_str)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name))
+    }
+}
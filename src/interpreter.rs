@@ -1,20 +1,55 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
-use crate::ast::{Stmt, Expr, Function, BinOp, LogicalOp, UnaryOp};
+use std::io::Write;
+use std::rc::Rc;
+use crate::builtins;
+use crate::env::Environment;
+use crate::span::Span;
+use crate::ast::{Program, Item, Stmt, StmtKind, SwitchCase, Initializer, Expr, ExprKind, Function, StructDef, EnumDef, Type, BinOp, LogicalOp, UnaryOp, UpdateOp};
+
+// O maior número de elementos de um array, somando todas as dimensões. Um tamanho maior é um erro
+// de execução, em vez de o processo abortar tentando reservar a memória
+const MAX_ARRAY_ELEMENTS: usize = 1 << 24;
+
+// Os elementos de um array, compartilhados entre todas as cópias do valor
+pub type ArrayElements = Rc<RefCell<Vec<Value>>>;
 
 // Os tipos de valores
 #[derive(Debug, Clone)]
 pub enum Value {
     Number(i64),
+    Float(f64),
     Bool(bool),
+    Str(String),
+    Char(char),
+    // Os elementos ficam compartilhados: copiar o valor (passando para uma função, por exemplo)
+    // copia só a referência, como acontece com arrays em C
+    Array(ArrayElements),
+    // Uma struct guarda os próprios campos, então copiar o valor copia a struct inteira
+    Struct(StructValue),
     Void,
 }
 
+// O valor de uma struct: o nome do tipo e os campos, na ordem da definição
+#[derive(Debug, Clone)]
+pub struct StructValue {
+    pub name: String,
+    pub fields: Vec<(String, Value)>,
+}
+
 //O tratamento desses valores
 impl Value {
-    fn as_number(&self) -> i64 {
+    // O nome do tipo do valor, como é escrito no código
+    pub fn type_name(&self) -> &'static str {
         match self {
-            Value::Number(n) => *n,
-            _ => panic!("Tentativa de usar não-número como número"),
+            Value::Number(_) => "int",
+            Value::Float(_) => "double",
+            Value::Bool(_) => "bool",
+            Value::Str(_) => "string",
+            Value::Char(_) => "char",
+            Value::Array(_) => "array",
+            Value::Struct(_) => "struct",
+            Value::Void => "void",
         }
     }
 
@@ -22,172 +57,470 @@ impl Value {
         match self {
             Value::Bool(b) => *b,
             Value::Number(n) => *n != 0,
+            Value::Float(f) => *f != 0.0,
+            Value::Str(s) => !s.is_empty(),
+            Value::Char(c) => *c != '\0',
+            Value::Array(_) | Value::Struct(_) => true,
             Value::Void => false,
         }
     }
 }
 
+// Como o valor aparece para o usuário
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            // um double inteiro aparece com o ".0", para não ser confundido com um int
+            Value::Float(x) if x.is_finite() && x.fract() == 0.0 => write!(f, "{:.1}", x),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Char(c) => write!(f, "{}", c),
+            Value::Array(elements) => {
+                let elements: Vec<String> = elements.borrow().iter().map(Value::to_string).collect();
+                write!(f, "{{{}}}", elements.join(", "))
+            }
+            Value::Struct(value) => {
+                let fields: Vec<String> =
+                    value.fields.iter().map(|(name, field)| format!("{}: {}", name, field)).collect();
+                write!(f, "{} {{{}}}", value.name, fields.join(", "))
+            }
+            Value::Void => write!(f, "void"),
+        }
+    }
+}
+
+// O que acontece quando uma conta entre inteiros não cabe em 64 bits: um erro de execução (o
+// padrão), o valor que dá a volta, como no complemento de dois, ou o maior/menor valor possível
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowMode {
+    #[default]
+    Trap,
+    Wrap,
+    Saturate,
+}
+
 pub struct Interpreter {
     globals: HashMap<String, Function>,
-    locals: HashMap<String, Value>,
+    global_vars: HashMap<String, Value>,
+    structs: HashMap<String, StructDef>,
+    // As constantes de todos os enums, que valem como variáveis int que não podem ser alteradas
+    constants: HashMap<String, i64>,
+    locals: Environment,
+    // Para onde vai o que o programa imprime com print, println e printf
+    output: Box<dyn Write>,
+    overflow_mode: OverflowMode,
 }
 
+// Os erros de execução, cada um com o span da expressão ou statement que falhou
 #[derive(Debug)]
 pub enum RuntimeError {
-    UndefinedVariable(String),
-    UndefinedFunction(String),
-    WrongArgumentCount(String, usize, usize),
-    DivisionByZero,
-    Return(Value), // Usado para controle de fluxo do return
+    UndefinedVariable(String, Vec<String>, Span), // o nome e os nomes visíveis naquele ponto, para as sugestões
+    UndefinedFunction(String, Span),
+    WrongArgumentCount(String, usize, usize, Span),
+    DivisionByZero(Span),
+    IntegerOverflow(String, Span), // a conta que estourou, como em "9223372036854775807 + 1"
+    InvalidShift(i64, Span), // a quantidade de bits fora de 0 a 63
+    Redeclaration(String, Span),
+    IndexOutOfBounds { index: i64, len: usize, span: Span },
+    InvalidArray(String, Span), // tamanho negativo ou inicializador grande demais
+    UndefinedStruct(String, Span),
+    UndefinedField(String, String, Span), // a struct e o campo que ela não tem
+    AssignToConstant(String, Span),
+    BuiltinError(String, String, Span), // a função embutida e o que deu errado nela
+    TypeError(String, Span),            // a descrição do uso errado
+}
+
+// Como a execução de um statement terminou: normalmente, com o valor do último statement (que é o
+// que o REPL mostra), ou desviando o fluxo com um return, break ou continue, que sobe até a função,
+// o laço ou o switch que o trata
+enum ControlFlow {
+    Normal(Value),
+    // o valor devolvido e o trecho da expressão do return, para os erros de conversão
+    Return(Value, Span),
+    Break,
+    Continue,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
     pub fn new() -> Self {
+        Self::with_output(Box::new(std::io::stdout()))
+    }
+
+    // Um interpretador que escreve a saída do programa em `output` em vez da saída padrão
+    pub fn with_output(output: Box<dyn Write>) -> Self {
         Interpreter {
             globals: HashMap::new(),
-            locals: HashMap::new(),
+            global_vars: HashMap::new(),
+            structs: HashMap::new(),
+            constants: HashMap::new(),
+            locals: Environment::new(),
+            output,
+            overflow_mode: OverflowMode::default(),
         }
     }
 
-    pub fn interpret(&mut self, funcoes: Vec<Function>, main_body: Vec<Stmt>) -> Result<Value, RuntimeError> {
-        // Registra todas as funções
-        for func in funcoes {
-            self.globals.insert(func.name.clone(), func);
-        }
+    // Troca a saída do programa, mantendo as funções e variáveis já carregadas
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = output;
+    }
+
+    // Escolhe o que acontece quando uma conta entre inteiros estoura
+    pub fn set_overflow_mode(&mut self, mode: OverflowMode) {
+        self.overflow_mode = mode;
+    }
+
+    pub fn interpret(&mut self, program: Program) -> Result<Value, RuntimeError> {
+        self.interpret_with_args(program, Vec::new())
+    }
+
+    // Igual ao interpret, mas passando valores para os parâMetros da main (vindos da linha de
+    // comando, por exemplo)
+    pub fn interpret_with_args(&mut self, program: Program, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        self.load(program)?;
 
         // Executa o corpo da main
-        self.execute_statements(&main_body)
+        let main_span = self.globals.get("main").map_or(Span::default(), |main| main.span);
+        let result = self.call_with_values("main", args, main_span);
+        // Garante que o que foi impresso sem quebra de linha chegue à saída antes de encerrar
+        let _ = self.output.flush();
+        result
+    }
+
+    // Registra as funções e avalia as variáveis globais do programa, mas sem chamar a main
+    pub fn load(&mut self, program: Program) -> Result<(), RuntimeError> {
+        self.load_items(program, false)
     }
 
-    fn execute_statements(&mut self, statements: &[Stmt]) -> Result<Value, RuntimeError> {
+    // Igual ao load, mas as variáveis globais que já existem recebem o valor novo em vez de serem
+    // um erro, para que o mesmo arquivo possa ser carregado de novo depois de editado
+    pub fn reload(&mut self, program: Program) -> Result<(), RuntimeError> {
+        self.load_items(program, true)
+    }
+
+    fn load_items(&mut self, program: Program, replace: bool) -> Result<(), RuntimeError> {
+        let mut global_decls = Vec::new();
+
+        // Registra todas as funções, structs e enums, antes de qualquer coisa ser executada
+        for item in program.items {
+            match item {
+                Item::Function(func) => self.define_function(func),
+                Item::Struct(def) => self.define_struct(def),
+                Item::Enum(def) => self.define_enum(def),
+                Item::Global(stmt) => global_decls.push(stmt),
+            }
+        }
+
+        // Avalia as variáveis globais na ordem em que aparecem no arquivo
+        for stmt in &global_decls {
+            self.declare_global(stmt, replace)?;
+        }
+
+        Ok(())
+    }
+
+    // Define (ou redefine) uma função
+    pub fn define_function(&mut self, func: Function) {
+        self.globals.insert(func.name.clone(), func);
+    }
+
+    // Define (ou redefine) uma struct
+    pub fn define_struct(&mut self, def: StructDef) {
+        self.structs.insert(def.name.clone(), def);
+    }
+
+    // Registra as constantes de um enum
+    pub fn define_enum(&mut self, def: EnumDef) {
+        self.constants.extend(def.constants);
+    }
+
+    // Executa um statement solto, como no REPL. As variáveis declaradas viram globais, visíveis
+    // também nas funções, e continuam valendo nas próximas chamadas
+    pub fn run_statement(&mut self, stmt: &Stmt) -> Result<Value, RuntimeError> {
+        if matches!(stmt.kind, StmtKind::VarDecl { .. } | StmtKind::ArrayDecl { .. }) {
+            self.declare_global(stmt, false)?;
+            return Ok(Value::Void);
+        }
+        match self.execute_statement(stmt)? {
+            ControlFlow::Normal(value) | ControlFlow::Return(value, _) => Ok(value),
+            ControlFlow::Break | ControlFlow::Continue => Ok(Value::Void),
+        }
+    }
+
+    // Avalia uma expressão solta no estado atual do interpretador
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
+        self.evaluate_expression(expr)
+    }
+
+    // As funções definidas pelo usuário, em ordem alfabética
+    pub fn functions(&self) -> Vec<&Function> {
+        let mut functions: Vec<&Function> = self.globals.values().collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        functions
+    }
+
+    // Os nomes das funções que podem ser chamadas: as do usuário e as embutidas
+    pub fn function_names(&self) -> impl Iterator<Item = &str> {
+        self.globals.keys().map(String::as_str).chain(builtins::NAMES.iter().copied())
+    }
+
+    // Os nomes das variáveis globais e das constantes de enum
+    pub fn global_names(&self) -> impl Iterator<Item = &str> {
+        self.global_vars.keys().chain(self.constants.keys()).map(String::as_str)
+    }
+
+    // As constantes de enum já definidas, com os seus valores
+    pub fn enum_constants(&self) -> impl Iterator<Item = (&str, i64)> {
+        self.constants.iter().map(|(name, &value)| (name.as_str(), value))
+    }
+
+    // Os nomes dos campos da struct `name`, ou nenhum se ela não existir
+    pub fn field_names(&self, name: &str) -> impl Iterator<Item = &str> {
+        self.structs.get(name).into_iter().flat_map(|def| def.fields.iter().map(|field| field.name.as_str()))
+    }
+
+    // Declara uma variável global. Com `replace`, uma global que já existe recebe o valor novo
+    fn declare_global(&mut self, stmt: &Stmt, replace: bool) -> Result<(), RuntimeError> {
+        let (name, val) = match &stmt.kind {
+            StmtKind::VarDecl { name, ty, init } => (name, self.initial_value(ty, init.as_ref(), stmt.span)?),
+            StmtKind::ArrayDecl { name, ty, dims, init } => {
+                (name, self.new_array(name, ty, dims, init.as_ref(), stmt.span)?)
+            }
+            _ => return Ok(()),
+        };
+        if (self.global_vars.contains_key(name) && !replace) || self.constants.contains_key(name) {
+            return Err(RuntimeError::Redeclaration(name.clone(), stmt.span));
+        }
+        self.global_vars.insert(name.clone(), val);
+        Ok(())
+    }
+
+    // Executa os statements em sequência. Um return, break ou continue interrompe a sequência e
+    // sobe até quem sabe tratá-lo, para que um return dentro de um if ou laço encerre a função
+    // inteira
+    fn execute_statements(&mut self, statements: &[Stmt]) -> Result<ControlFlow, RuntimeError> {
         let mut last_value = Value::Void;
 
         for stmt in statements {
-            match self.execute_statement(stmt) {
-                Ok(value) => last_value = value,
-                Err(RuntimeError::Return(value)) => return Ok(value),
-                Err(e) => return Err(e),
+            match self.execute_statement(stmt)? {
+                ControlFlow::Normal(value) => last_value = value,
+                flow => return Ok(flow),
             }
         }
 
-        Ok(last_value)
+        Ok(ControlFlow::Normal(last_value))
+    }
+
+    // Executa um bloco { ... } dentro de um escopo próprio, que é descartado ao final
+    fn execute_block(&mut self, statements: &[Stmt]) -> Result<ControlFlow, RuntimeError> {
+        self.locals.push_scope();
+        let result = self.execute_statements(statements);
+        self.locals.pop_scope();
+        result
     }
 
-    fn execute_statement(&mut self, stmt: &Stmt) -> Result<Value, RuntimeError> {
+    fn execute_statement(&mut self, stmt: &Stmt) -> Result<ControlFlow, RuntimeError> {
         //Tal como havia nos exemplos do Lox, temos o uso de statements
-        match stmt {
-            Stmt::VarDecl { name, value } => {
-                let val = self.evaluate_expression(value)?;
-                self.locals.insert(name.clone(), val.clone());
-                Ok(val)
+        match &stmt.kind {
+            StmtKind::VarDecl { name, ty, init } => {
+                let val = self.initial_value(ty, init.as_ref(), stmt.span)?;
+                if !self.locals.declare(name, val.clone()) {
+                    return Err(RuntimeError::Redeclaration(name.clone(), stmt.span));
+                }
+                Ok(ControlFlow::Normal(val))
+            }
+            StmtKind::ArrayDecl { name, ty, dims, init } => {
+                let array = self.new_array(name, ty, dims, init.as_ref(), stmt.span)?;
+                if !self.locals.declare(name, array) {
+                    return Err(RuntimeError::Redeclaration(name.clone(), stmt.span));
+                }
+                Ok(ControlFlow::Normal(Value::Void))
             }
-            Stmt::Return(expr) => {
+            StmtKind::Return(expr) => {
                 let value = self.evaluate_expression(expr)?;
-                Err(RuntimeError::Return(value))
+                Ok(ControlFlow::Return(value, expr.span))
             }
-            Stmt::ExprStmt(expr) => {
-                self.evaluate_expression(expr)
+            StmtKind::ExprStmt(expr) => {
+                Ok(ControlFlow::Normal(self.evaluate_expression(expr)?))
             }
-            Stmt::If { condition, then_branch, else_branch } => {
+            StmtKind::If { condition, then_branch, else_branch } => {
                 let condition_value = self.evaluate_expression(condition)?;
                 if condition_value.is_truthy() {
-                    self.execute_statements(then_branch)
+                    self.execute_block(then_branch)
                 } else if let Some(else_stmts) = else_branch {
-                    self.execute_statements(else_stmts)
+                    self.execute_block(else_stmts)
                 } else {
-                    Ok(Value::Void)
+                    Ok(ControlFlow::Normal(Value::Void))
                 }
             }
-            Stmt::While { condition, body } => {
+            StmtKind::While { condition, body } => {
                 let mut last_value = Value::Void;
                 loop {
                     let condition_value = self.evaluate_expression(condition)?;
                     if !condition_value.is_truthy() {
                         break;
                     }
-                    last_value = self.execute_statements(body)?;
+                    match self.execute_block(body)? {
+                        ControlFlow::Normal(value) => last_value = value,
+                        ControlFlow::Continue => {}
+                        ControlFlow::Break => break,
+                        flow @ ControlFlow::Return(..) => return Ok(flow),
+                    }
                 }
-                Ok(last_value)
+                Ok(ControlFlow::Normal(last_value))
             }
-            Stmt::For { init, condition, update, body } => {
+            StmtKind::DoWhile { body, condition } => {
+                // Igual ao while, mas a condição só é testada depois de cada volta, então o corpo
+                // é executado pelo menos uma vez. Um continue vai direto para a condição
                 let mut last_value = Value::Void;
+                loop {
+                    match self.execute_block(body)? {
+                        ControlFlow::Normal(value) => last_value = value,
+                        ControlFlow::Continue => {}
+                        ControlFlow::Break => break,
+                        flow @ ControlFlow::Return(..) => return Ok(flow),
+                    }
+                    if !self.evaluate_expression(condition)?.is_truthy() {
+                        break;
+                    }
+                }
+                Ok(ControlFlow::Normal(last_value))
+            }
+            StmtKind::For { init, condition, update, body } => {
+                // A variável da inicialização vive num escopo só do for
+                self.locals.push_scope();
+                let result = self.execute_for(init.as_deref(), condition.as_ref(), update.as_ref(), body);
+                self.locals.pop_scope();
+                result
+            }
+            StmtKind::Switch { subject, cases } => {
+                // O corpo inteiro do switch é um escopo só, como em C
+                self.locals.push_scope();
+                let result = self.execute_switch(subject, cases);
+                self.locals.pop_scope();
+                // o break encerra o switch, mas um continue é do laço em volta dele
+                match result? {
+                    ControlFlow::Break => Ok(ControlFlow::Normal(Value::Void)),
+                    flow => Ok(flow),
+                }
+            }
+            StmtKind::Break => Ok(ControlFlow::Break),
+            StmtKind::Continue => Ok(ControlFlow::Continue),
+        }
+    }
+
+    // Procura o case com o valor (ou o default, se nenhum bater) e executa a partir dele, passando
+    // pelos cases seguintes até um break ou o fim do switch
+    fn execute_switch(&mut self, subject: &Expr, cases: &[SwitchCase]) -> Result<ControlFlow, RuntimeError> {
+        let value = self.evaluate_expression(subject)?;
+        let n = Self::integer(&value).ok_or_else(|| {
+            RuntimeError::TypeError(
+                format!("o switch precisa de um int ou char, mas recebeu {}", value.type_name()),
+                subject.span,
+            )
+        })?;
 
-                // Executa inicialização, se houver
-                if let Some(init_stmt) = init {
-                    self.execute_statement(init_stmt)?;
+        let start = cases
+            .iter()
+            .position(|case| case.label == Some(n))
+            .or_else(|| cases.iter().position(|case| case.label.is_none()));
+        let mut last_value = Value::Void;
+        if let Some(start) = start {
+            for case in &cases[start..] {
+                match self.execute_statements(&case.body)? {
+                    ControlFlow::Normal(value) => last_value = value,
+                    flow => return Ok(flow),
                 }
+            }
+        }
+        Ok(ControlFlow::Normal(last_value))
+    }
 
-                loop {
-                    // Verifica condição, caso haja
-                    if let Some(cond_expr) = condition {
-                        let condition_value = self.evaluate_expression(cond_expr)?;
-                        if !condition_value.is_truthy() {
-                            break;
-                        }
-                    }
+    fn execute_for(
+        &mut self,
+        init: Option<&Stmt>,
+        condition: Option<&Expr>,
+        update: Option<&Expr>,
+        body: &[Stmt],
+    ) -> Result<ControlFlow, RuntimeError> {
+        let mut last_value = Value::Void;
 
-                    // Executa o corpo do código
-                    last_value = self.execute_statements(body)?;
+        // Executa inicialização, se houver
+        if let Some(init_stmt) = init {
+            self.execute_statement(init_stmt)?;
+        }
 
-                    // Executa atualização, se tiver
-                    if let Some(update_expr) = update {
-                        self.evaluate_expression(update_expr)?;
-                    }
+        loop {
+            // Verifica condição, caso haja
+            if let Some(cond_expr) = condition {
+                let condition_value = self.evaluate_expression(cond_expr)?;
+                if !condition_value.is_truthy() {
+                    break;
                 }
+            }
 
-                Ok(last_value)
+            // Executa o corpo do código. Depois de um continue a atualização ainda acontece
+            match self.execute_block(body)? {
+                ControlFlow::Normal(value) => last_value = value,
+                ControlFlow::Continue => {}
+                ControlFlow::Break => break,
+                flow @ ControlFlow::Return(..) => return Ok(flow),
+            }
+
+            // Executa atualização, se tiver
+            if let Some(update_expr) = update {
+                self.evaluate_expression(update_expr)?;
             }
         }
+
+        Ok(ControlFlow::Normal(last_value))
     }
 
     fn evaluate_expression(&mut self, expr: &Expr) -> Result<Value, RuntimeError> {
-        match expr {
-            Expr::Number(n) => Ok(Value::Number(*n)),
+        match &expr.kind {
+            ExprKind::Number(n) => Ok(Value::Number(*n)),
 
-            Expr::Bool(b) => Ok(Value::Bool(*b)),
+            ExprKind::Float(f) => Ok(Value::Float(*f)),
 
-            Expr::Var(name) => {
-                if let Some(value) = self.locals.get(name) {
-                    Ok(value.clone())
-                } else {
-                    Err(RuntimeError::UndefinedVariable(name.clone()))
-                }
-            }
+            ExprKind::Bool(b) => Ok(Value::Bool(*b)),
+
+            ExprKind::Str(s) => Ok(Value::Str(s.clone())),
 
-            Expr::Binary { op, lhs, rhs } => {
+            ExprKind::Char(c) => Ok(Value::Char(*c)),
+
+            ExprKind::Var(name) => self.read_variable(name, expr.span),
+
+            ExprKind::Binary { op, lhs, rhs } => {
                 let left_val = self.evaluate_expression(lhs)?;
                 let right_val = self.evaluate_expression(rhs)?;
 
                 match op {
-                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div => {
-                        let left_num = left_val.as_number();
-                        let right_num = right_val.as_number();
-
-                        let result = match op {
-                            BinOp::Add => left_num + right_num,
-                            BinOp::Sub => left_num - right_num,
-                            BinOp::Mul => left_num * right_num,
-                            BinOp::Div => {
-                                if right_num == 0 {
-                                    return Err(RuntimeError::DivisionByZero);
-                                }
-                                left_num / right_num
-                            }
-                            _ => unreachable!(),
-                        };
-
-                        Ok(Value::Number(result))
-                    }
+                    BinOp::Add
+                    | BinOp::Sub
+                    | BinOp::Mul
+                    | BinOp::Div
+                    | BinOp::Mod
+                    | BinOp::BitAnd
+                    | BinOp::BitOr
+                    | BinOp::BitXor
+                    | BinOp::ShiftLeft
+                    | BinOp::ShiftRight => self.arithmetic(*op, &left_val, &right_val, expr.span),
                     BinOp::Equal => Ok(Value::Bool(self.values_equal(&left_val, &right_val))),
                     BinOp::NotEqual => Ok(Value::Bool(!self.values_equal(&left_val, &right_val))),
-                    BinOp::Greater => Ok(Value::Bool(left_val.as_number() > right_val.as_number())),
-                    BinOp::GreaterEqual => Ok(Value::Bool(left_val.as_number() >= right_val.as_number())),
-                    BinOp::Less => Ok(Value::Bool(left_val.as_number() < right_val.as_number())),
-                    BinOp::LessEqual => Ok(Value::Bool(left_val.as_number() <= right_val.as_number())),
+                    BinOp::Greater | BinOp::GreaterEqual | BinOp::Less | BinOp::LessEqual => {
+                        self.compare(*op, &left_val, &right_val, expr.span)
+                    }
                 }
             }
 
-            Expr::Logical { op, lhs, rhs } => {
+            ExprKind::Logical { op, lhs, rhs } => {
                 let left_val = self.evaluate_expression(lhs)?;
 
                 match op {
@@ -208,58 +541,723 @@ impl Interpreter {
                 }
             }
 
-            Expr::Unary { op, expr } => {
+            ExprKind::Unary { op, expr } => {
                 let val = self.evaluate_expression(expr)?;
 
                 match op {
                     UnaryOp::Not => Ok(Value::Bool(!val.is_truthy())),
-                    UnaryOp::Minus => Ok(Value::Number(-val.as_number())),
+                    UnaryOp::Minus => match val {
+                        Value::Number(n) => match n.checked_neg() {
+                            Some(negated) => Ok(Value::Number(negated)),
+                            None => {
+                                let operation = format!("-({})", n);
+                                Ok(Value::Number(self.overflow(BinOp::Sub, 0, n, operation, expr.span)?))
+                            }
+                        },
+                        Value::Float(f) => Ok(Value::Float(-f)),
+                        Value::Char(c) => Ok(Value::Number(-(c as i64))),
+                        other => Err(RuntimeError::TypeError(
+                            format!("o operador '-' não pode ser usado com {}", other.type_name()),
+                            expr.span,
+                        )),
+                    },
+                    UnaryOp::BitNot => match Self::integer(&val) {
+                        Some(n) => Ok(Value::Number(!n)),
+                        None => Err(RuntimeError::TypeError(
+                            format!("o operador '~' não pode ser usado com {}", val.type_name()),
+                            expr.span,
+                        )),
+                    },
+                }
+            }
+
+            ExprKind::Conditional { condition, then_expr, else_expr } => {
+                if self.evaluate_expression(condition)?.is_truthy() {
+                    self.evaluate_expression(then_expr)
+                } else {
+                    self.evaluate_expression(else_expr)
                 }
             }
 
-            Expr::Call { name, args } => {
-                self.call_function(name, args)
+            ExprKind::Cast { ty, expr: inner } => {
+                let val = self.evaluate_expression(inner)?;
+                Self::cast(val, ty, expr.span)
+            }
+
+            ExprKind::Call { name, args } => {
+                self.call_function(name, args, expr.span)
+            }
+
+            ExprKind::Index { array, index } => {
+                let (elements, i) = self.element(array, index, expr.span)?;
+                let value = elements.borrow()[i].clone();
+                Ok(value)
+            }
+
+            ExprKind::Field { object, field } => {
+                let value = self.evaluate_expression(object)?;
+                let i = Self::field_index(&value, field, expr.span)?;
+                match value {
+                    Value::Struct(mut value) => Ok(value.fields.swap_remove(i).1),
+                    _ => unreachable!(),
+                }
+            }
+
+            ExprKind::Assign { target, value } => {
+                let place = self.resolve_place(target)?;
+                let val = self.evaluate_expression(value)?;
+                self.write_place(&place, val, expr.span)
+            }
+
+            ExprKind::CompoundAssign { target, op, value } => {
+                let place = self.resolve_place(target)?;
+                let current = self.read_place(&place, expr.span)?;
+                let rhs = self.evaluate_expression(value)?;
+                let result = self.arithmetic(*op, &current, &rhs, expr.span)?;
+                self.write_place(&place, result, expr.span)
+            }
+
+            ExprKind::PrefixUpdate { op, target } => {
+                let place = self.resolve_place(target)?;
+                let current = self.read_place(&place, expr.span)?;
+                let updated = self.arithmetic(Self::update_binop(*op), &current, &Value::Number(1), expr.span)?;
+                self.write_place(&place, updated, expr.span)
+            }
+
+            ExprKind::PostfixUpdate { op, target } => {
+                let place = self.resolve_place(target)?;
+                let current = self.read_place(&place, expr.span)?;
+                let updated = self.arithmetic(Self::update_binop(*op), &current, &Value::Number(1), expr.span)?;
+                self.write_place(&place, updated, expr.span)?;
+                Ok(current)
             }
         }
     }
 
+    // Cria o array de uma declaração. Os tamanhos de todas as dimensões são avaliados antes dos
+    // valores do inicializador
+    fn new_array(
+        &mut self,
+        name: &str,
+        ty: &Type,
+        dims: &[Option<Expr>],
+        init: Option<&Initializer>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let mut sizes = Vec::with_capacity(dims.len());
+        let mut total: usize = 1;
+        for dim in dims {
+            sizes.push(match dim {
+                Some(expr) => {
+                    let size = self.array_size(expr)?;
+                    total = total.checked_mul(size).filter(|&total| total <= MAX_ARRAY_ELEMENTS).ok_or_else(|| {
+                        RuntimeError::InvalidArray(
+                            format!("o array '{}' passaria do limite de {} elementos", name, MAX_ARRAY_ELEMENTS),
+                            expr.span,
+                        )
+                    })?;
+                    Some(size)
+                }
+                None => None,
+            });
+        }
+        self.build_array(name, ty, &sizes, init, span)
+    }
+
+    // Monta uma dimensão do array: cada elemento é um valor (na última dimensão) ou outro array.
+    // O que não está no inicializador começa zerado, e cada linha é um array próprio
+    fn build_array(
+        &mut self,
+        name: &str,
+        ty: &Type,
+        sizes: &[Option<usize>],
+        init: Option<&Initializer>,
+        span: Span,
+    ) -> Result<Value, RuntimeError> {
+        let (items, list_span): (&[Initializer], _) = match init {
+            Some(Initializer::List(items, span)) => (items, Some(*span)),
+            Some(Initializer::Value(expr)) => {
+                return Err(RuntimeError::InvalidArray(
+                    format!("o array '{}' tem mais dimensões, então aqui era esperada uma lista entre chaves", name),
+                    expr.span,
+                ));
+            }
+            None => (&[], None),
+        };
+        let len = sizes[0].unwrap_or(items.len());
+        if let Some(span) = list_span.filter(|_| items.len() > len) {
+            return Err(RuntimeError::InvalidArray(
+                format!("o array '{}' tem tamanho {}, mas o inicializador tem {} valores", name, len, items.len()),
+                span,
+            ));
+        }
+        let inner = &sizes[1..];
+
+        let mut elements = Vec::new();
+        elements.try_reserve(len).map_err(|_| {
+            RuntimeError::InvalidArray(format!("não há memória para os {} elementos do array '{}'", len, name), span)
+        })?;
+        for item in items {
+            let element = match item {
+                Initializer::List(_, list_span) if inner.is_empty() && !matches!(ty, Type::Struct(_)) => {
+                    return Err(RuntimeError::InvalidArray(
+                        format!("o array '{}' não tem tantas dimensões quanto as chaves do inicializador", name),
+                        *list_span,
+                    ));
+                }
+                _ if inner.is_empty() => self.initial_value(ty, Some(item), span)?,
+                _ => self.build_array(name, ty, inner, Some(item), span)?,
+            };
+            elements.push(element);
+        }
+        while elements.len() < len {
+            let element = if inner.is_empty() {
+                self.initial_value(ty, None, span)?
+            } else {
+                self.build_array(name, ty, inner, None, span)?
+            };
+            elements.push(element);
+        }
+        Ok(Value::Array(Rc::new(RefCell::new(elements))))
+    }
+
+    fn array_size(&mut self, expr: &Expr) -> Result<usize, RuntimeError> {
+        let value = self.evaluate_expression(expr)?;
+        let size = Self::integer(&value).ok_or_else(|| {
+            RuntimeError::TypeError(
+                format!("o tamanho do array precisa ser um int, mas é {}", value.type_name()),
+                expr.span,
+            )
+        })?;
+        usize::try_from(size).map_err(|_| {
+            RuntimeError::InvalidArray(format!("o tamanho do array não pode ser negativo ({})", size), expr.span)
+        })
+    }
+
+    // O valor inicial de uma variável, elemento de array ou campo do tipo `ty`: o inicializador
+    // convertido para o tipo, ou o valor zerado quando não há inicializador
+    fn initial_value(&mut self, ty: &Type, init: Option<&Initializer>, span: Span) -> Result<Value, RuntimeError> {
+        match (ty, init) {
+            (_, Some(Initializer::Value(expr))) => {
+                let value = self.evaluate_expression(expr)?;
+                Self::coerce(value, ty, expr.span)
+            }
+            (Type::Struct(name), Some(Initializer::List(items, list_span))) => {
+                self.build_struct(name, items, *list_span)
+            }
+            (Type::Struct(name), None) => self.build_struct(name, &[], span),
+            (_, Some(Initializer::List(_, list_span))) => Err(RuntimeError::TypeError(
+                format!("um {} não pode ser inicializado com uma lista entre chaves", ty),
+                *list_span,
+            )),
+            (Type::Int, None) => Ok(Value::Number(0)),
+            (Type::Double, None) => Ok(Value::Float(0.0)),
+            (Type::Bool, None) => Ok(Value::Bool(false)),
+            (Type::Char, None) => Ok(Value::Char('\0')),
+            (Type::String, None) => Ok(Value::Str(String::new())),
+        }
+    }
+
+    // Monta uma struct com os valores da lista, na ordem dos campos. Os campos que ficaram de fora
+    // começam zerados
+    fn build_struct(&mut self, name: &str, items: &[Initializer], span: Span) -> Result<Value, RuntimeError> {
+        let def = self
+            .structs
+            .get(name)
+            .cloned()
+            .ok_or_else(|| RuntimeError::UndefinedStruct(name.to_string(), span))?;
+        if items.len() > def.fields.len() {
+            return Err(RuntimeError::TypeError(
+                format!(
+                    "a struct {} tem {} campos, mas o inicializador tem {} valores",
+                    name,
+                    def.fields.len(),
+                    items.len()
+                ),
+                span,
+            ));
+        }
+
+        let mut fields = Vec::with_capacity(def.fields.len());
+        for (i, field) in def.fields.iter().enumerate() {
+            let value = self.initial_value(&field.ty, items.get(i), span)?;
+            fields.push((field.name.clone(), value));
+        }
+        Ok(Value::Struct(StructValue { name: def.name, fields }))
+    }
+
+    // A posição do campo `field` na struct `value`
+    fn field_index(value: &Value, field: &str, span: Span) -> Result<usize, RuntimeError> {
+        match value {
+            Value::Struct(value) => value
+                .fields
+                .iter()
+                .position(|(name, _)| name == field)
+                .ok_or_else(|| RuntimeError::UndefinedField(value.name.clone(), field.to_string(), span)),
+            other => Err(RuntimeError::TypeError(
+                format!("não é possível acessar o campo '{}' de um valor do tipo {}", field, other.type_name()),
+                span,
+            )),
+        }
+    }
+
+    // Avalia v[i] até achar o array e a posição do elemento, conferindo os limites
+    fn element(&mut self, array: &Expr, index: &Expr, span: Span) -> Result<(ArrayElements, usize), RuntimeError> {
+        let elements = match self.evaluate_expression(array)? {
+            Value::Array(elements) => elements,
+            other => {
+                return Err(RuntimeError::TypeError(
+                    format!("não é possível indexar um valor do tipo {}", other.type_name()),
+                    array.span,
+                ));
+            }
+        };
+
+        let index_value = self.evaluate_expression(index)?;
+        let i = Self::integer(&index_value).ok_or_else(|| {
+            RuntimeError::TypeError(
+                format!("o índice precisa ser um int, mas é {}", index_value.type_name()),
+                index.span,
+            )
+        })?;
+
+        let len = elements.borrow().len();
+        match usize::try_from(i) {
+            Ok(position) if position < len => Ok((elements, position)),
+            _ => Err(RuntimeError::IndexOutOfBounds { index: i, len, span }),
+        }
+    }
+
+    // Descobre onde fica o alvo de uma atribuição, avaliando o índice uma única vez (em v[i++] += 1
+    // o i só é incrementado uma vez)
+    fn resolve_place(&mut self, target: &Expr) -> Result<Place, RuntimeError> {
+        match &target.kind {
+            ExprKind::Var(name) => Ok(Place { root: PlaceRoot::Variable(name.clone()), fields: Vec::new() }),
+            ExprKind::Index { array, index } => {
+                let (elements, i) = self.element(array, index, target.span)?;
+                Ok(Place { root: PlaceRoot::Element(elements, i), fields: Vec::new() })
+            }
+            ExprKind::Field { object, field } => {
+                let mut place = self.resolve_place(object)?;
+                let i = Self::field_index(&self.read_place(&place, object.span)?, field, target.span)?;
+                place.fields.push(i);
+                Ok(place)
+            }
+            _ => Err(RuntimeError::TypeError(
+                "só é possível atribuir a variáveis, elementos de array e campos".to_string(),
+                target.span,
+            )),
+        }
+    }
+
+    fn read_place(&self, place: &Place, span: Span) -> Result<Value, RuntimeError> {
+        let value = match &place.root {
+            PlaceRoot::Variable(name) => self.read_variable(name, span)?,
+            PlaceRoot::Element(elements, i) => elements.borrow()[*i].clone(),
+        };
+        Ok(place.fields.iter().fold(value, |value, &i| match value {
+            Value::Struct(mut value) => value.fields.swap_remove(i).1,
+            _ => unreachable!(),
+        }))
+    }
+
+    fn write_place(&mut self, place: &Place, value: Value, span: Span) -> Result<Value, RuntimeError> {
+        match &place.root {
+            PlaceRoot::Variable(name) if place.fields.is_empty() => self.assign_variable(name, value, span),
+            PlaceRoot::Variable(name) => {
+                let slot = self.variable_slot(name, span)?;
+                Self::store(Self::field_slot(slot, &place.fields), value, span)
+            }
+            PlaceRoot::Element(elements, i) => {
+                let mut elements = elements.borrow_mut();
+                Self::store(Self::field_slot(&mut elements[*i], &place.fields), value, span)
+            }
+        }
+    }
+
+    // Desce pelos campos de uma struct até o campo que vai receber a atribuição
+    fn field_slot<'a>(mut slot: &'a mut Value, fields: &[usize]) -> &'a mut Value {
+        for &i in fields {
+            slot = match slot {
+                Value::Struct(value) => &mut value.fields[i].1,
+                _ => unreachable!(),
+            };
+        }
+        slot
+    }
+
+    fn store(slot: &mut Value, value: Value, span: Span) -> Result<Value, RuntimeError> {
+        let value = Self::convert_for_slot(slot, value, span)?;
+        *slot = value.clone();
+        Ok(value)
+    }
+
+    // Lê o valor atual de uma variável já declarada, primeiro nos escopos locais, depois nas globais
+    // e por último nas constantes de enum
+    fn read_variable(&self, name: &str, span: Span) -> Result<Value, RuntimeError> {
+        if let Some(value) = self.locals.get(name).or_else(|| self.global_vars.get(name)) {
+            return Ok(value.clone());
+        }
+        self.constants
+            .get(name)
+            .map(|&n| Value::Number(n))
+            .ok_or_else(|| self.undefined_variable(name, span))
+    }
+
+    // O erro de variável não definida, com os nomes visíveis naquele ponto (locais, globais e
+    // constantes de enum), já que os locais deixam de existir quando a função termina
+    fn undefined_variable(&self, name: &str, span: Span) -> RuntimeError {
+        let names = self
+            .locals
+            .names()
+            .chain(self.global_vars.keys().chain(self.constants.keys()).map(String::as_str))
+            .map(String::from)
+            .collect();
+        RuntimeError::UndefinedVariable(name.to_string(), names, span)
+    }
+
+    // Atribui um valor novo a uma variável já declarada e devolve esse valor. Como a variável
+    // guarda um valor do tipo com que foi declarada, um int recebendo um double o trunca, um
+    // double recebendo um int o converte e um char recebendo um int vira o caractere do código
+    fn assign_variable(&mut self, name: &str, value: Value, span: Span) -> Result<Value, RuntimeError> {
+        let slot = self.variable_slot(name, span)?;
+        Self::store(slot, value, span)
+    }
+
+    fn variable_slot(&mut self, name: &str, span: Span) -> Result<&mut Value, RuntimeError> {
+        if self.locals.get(name).is_none() && !self.global_vars.contains_key(name) {
+            if self.constants.contains_key(name) {
+                return Err(RuntimeError::AssignToConstant(name.to_string(), span));
+            }
+            return Err(self.undefined_variable(name, span));
+        }
+        match self.locals.get_mut(name) {
+            Some(slot) => Ok(slot),
+            None => Ok(self.global_vars.get_mut(name).expect("a variável foi encontrada acima")),
+        }
+    }
+
+    // O valor convertido para o tipo do que está guardado em `slot`, que é o tipo com que a
+    // variável ou o array foi declarado
+    fn convert_for_slot(slot: &Value, value: Value, span: Span) -> Result<Value, RuntimeError> {
+        match slot {
+            Value::Number(_) => Self::coerce(value, &Type::Int, span),
+            Value::Float(_) => Self::coerce(value, &Type::Double, span),
+            Value::Char(_) => Self::coerce(value, &Type::Char, span),
+            Value::Bool(_) => Self::coerce(value, &Type::Bool, span),
+            Value::Str(_) => Self::coerce(value, &Type::String, span),
+            Value::Struct(slot) => Self::coerce(value, &Type::Struct(slot.name.clone()), span),
+            Value::Array(_) => Err(RuntimeError::TypeError(
+                "um array não pode ser atribuído de uma vez, só os seus elementos".to_string(),
+                span,
+            )),
+            Value::Void => Ok(value),
+        }
+    }
+
+    // As conversões implícitas feitas ao guardar um valor numa variável, parâMetro ou retorno do tipo
+    // `ty`: entre int, double e char, como em C, com bool valendo 0 ou 1 e um número virando bool
+    // por ser diferente de zero. Um valor do próprio tipo (ou uma struct do mesmo tipo) fica como
+    // está, e qualquer outra combinação, como texto para int, é um erro
+    fn coerce(value: Value, ty: &Type, span: Span) -> Result<Value, RuntimeError> {
+        match (ty, value) {
+            (_, Value::Void) => Ok(Value::Void),
+            (Type::Int, value @ Value::Number(_))
+            | (Type::Double, value @ Value::Float(_))
+            | (Type::Char, value @ Value::Char(_))
+            | (Type::Bool, value @ Value::Bool(_))
+            | (Type::String, value @ Value::Str(_)) => Ok(value),
+            (Type::Struct(name), Value::Struct(value)) if value.name == *name => Ok(Value::Struct(value)),
+            (Type::Int, Value::Float(f)) => Ok(Value::Number(f as i64)),
+            (Type::Int, Value::Char(c)) => Ok(Value::Number(c as i64)),
+            (Type::Double, Value::Number(n)) => Ok(Value::Float(n as f64)),
+            (Type::Double, Value::Char(c)) => Ok(Value::Float(c as u32 as f64)),
+            (Type::Char, Value::Number(n)) => Self::char_from_code(n, span),
+            (Type::Char, Value::Float(f)) => Self::char_from_code(f as i64, span),
+            (Type::Int, Value::Bool(b)) => Ok(Value::Number(i64::from(b))),
+            (Type::Double, Value::Bool(b)) => Ok(Value::Float(if b { 1.0 } else { 0.0 })),
+            (Type::Char, Value::Bool(b)) => Self::char_from_code(i64::from(b), span),
+            (Type::Bool, value @ (Value::Number(_) | Value::Float(_) | Value::Char(_))) => Ok(Value::Bool(value.is_truthy())),
+            (_, Value::Struct(value)) => Err(RuntimeError::TypeError(
+                format!("não é possível converter struct {} para {}", value.name, ty),
+                span,
+            )),
+            (_, value) => Err(RuntimeError::TypeError(
+                format!("não é possível converter {} para {}", value.type_name(), ty),
+                span,
+            )),
+        }
+    }
+
+    // A conversão explícita (tipo)valor. Entre int, double, char e bool tudo é permitido, como em
+    // C; textos só podem ser convertidos para string
+    fn cast(value: Value, ty: &Type, span: Span) -> Result<Value, RuntimeError> {
+        let converted = match (ty, &value) {
+            (Type::Int, Value::Number(_))
+            | (Type::Double, Value::Float(_))
+            | (Type::Bool, Value::Bool(_))
+            | (Type::Char, Value::Char(_))
+            | (Type::String, Value::Str(_)) => value,
+            (Type::Int, Value::Float(f)) => Value::Number(*f as i64),
+            (Type::Int, Value::Bool(b)) => Value::Number(i64::from(*b)),
+            (Type::Int, Value::Char(c)) => Value::Number(*c as i64),
+            (Type::Double, Value::Number(_) | Value::Char(_)) => Value::Float(Self::as_float(&value).unwrap()),
+            (Type::Double, Value::Bool(b)) => Value::Float(if *b { 1.0 } else { 0.0 }),
+            (Type::Bool, Value::Number(_) | Value::Float(_) | Value::Char(_)) => Value::Bool(value.is_truthy()),
+            (Type::Char, Value::Number(n)) => Self::char_from_code(*n, span)?,
+            (Type::Char, Value::Float(f)) => Self::char_from_code(*f as i64, span)?,
+            (Type::Char, Value::Bool(b)) => Value::Char(if *b { '\u{1}' } else { '\0' }),
+            _ => {
+                return Err(RuntimeError::TypeError(
+                    format!("não é possível converter {} para {}", value.type_name(), ty),
+                    span,
+                ));
+            }
+        };
+        Ok(converted)
+    }
+
+    // O caractere cujo código Unicode é `code`
+    fn char_from_code(code: i64, span: Span) -> Result<Value, RuntimeError> {
+        u32::try_from(code)
+            .ok()
+            .and_then(char::from_u32)
+            .map(Value::Char)
+            .ok_or_else(|| {
+                RuntimeError::TypeError(format!("{} não é o código de um caractere válido", code), span)
+            })
+    }
+
+    // O valor como inteiro, se for um int, um char (que numa conta vale o seu código, como em C) ou um
+    // bool (que vale 0 ou 1)
+    fn integer(value: &Value) -> Option<i64> {
+        match value {
+            Value::Number(n) => Some(*n),
+            Value::Char(c) => Some(*c as i64),
+            Value::Bool(b) => Some(i64::from(*b)),
+            _ => None,
+        }
+    }
+
+    // O valor como double, se for numérico
+    fn as_float(value: &Value) -> Option<f64> {
+        match value {
+            Value::Float(f) => Some(*f),
+            _ => Self::integer(value).map(|n| n as f64),
+        }
+    }
+
+    // As operações aritméticas, compartilhadas entre expressões binárias e atribuições compostas.
+    // Entre textos (ou um texto e um char), o + concatena. Como em C, se um dos lados for double o outro é convertido
+    // e a conta é feita em double (então 7 / 2 é 3, mas 7 / 2.0 é 3.5). Os operadores bit a bit e
+    // os deslocamentos só existem entre inteiros
+    fn arithmetic(&self, op: BinOp, left: &Value, right: &Value, span: Span) -> Result<Value, RuntimeError> {
+        let (left_num, right_num) = match (left, right) {
+            (Value::Str(_), Value::Str(_) | Value::Char(_)) | (Value::Char(_), Value::Str(_))
+                if matches!(op, BinOp::Add) =>
+            {
+                return Ok(Value::Str(format!("{}{}", left, right)));
+            }
+            _ => match (Self::integer(left), Self::integer(right)) {
+                (Some(l), Some(r)) => (l, r),
+                _ => return Self::float_arithmetic(op, left, right, span),
+            },
+        };
+
+        let result = match op {
+            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Mod => {
+                if matches!(op, BinOp::Div | BinOp::Mod) && right_num == 0 {
+                    return Err(RuntimeError::DivisionByZero(span));
+                }
+                let checked = match op {
+                    BinOp::Add => left_num.checked_add(right_num),
+                    BinOp::Sub => left_num.checked_sub(right_num),
+                    BinOp::Mul => left_num.checked_mul(right_num),
+                    BinOp::Div => left_num.checked_div(right_num),
+                    _ => left_num.checked_rem(right_num),
+                };
+                match checked {
+                    Some(result) => result,
+                    None => {
+                        let operation = format!("{} {} {}", left_num, op, right_num);
+                        self.overflow(op, left_num, right_num, operation, span)?
+                    }
+                }
+            }
+            BinOp::BitAnd => left_num & right_num,
+            BinOp::BitOr => left_num | right_num,
+            BinOp::BitXor => left_num ^ right_num,
+            // deslocar 64 bits ou mais (ou um número negativo de bits) não é definido em C
+            BinOp::ShiftLeft | BinOp::ShiftRight => {
+                let bits = u32::try_from(right_num)
+                    .ok()
+                    .filter(|bits| *bits < i64::BITS)
+                    .ok_or(RuntimeError::InvalidShift(right_num, span))?;
+                if matches!(op, BinOp::ShiftLeft) {
+                    left_num << bits
+                } else {
+                    left_num >> bits
+                }
+            }
+            _ => unreachable!(),
+        };
+
+        Ok(Value::Number(result))
+    }
+
+    // O resultado de `left op right` quando ele não cabe em 64 bits, de acordo com o modo de estouro.
+    // Além das contas que passam do limite, isso acontece em i64::MIN / -1 e em -i64::MIN (que é
+    // tratado como 0 - i64::MIN)
+    fn overflow(&self, op: BinOp, left: i64, right: i64, operation: String, span: Span) -> Result<i64, RuntimeError> {
+        let result = match (self.overflow_mode, op) {
+            (OverflowMode::Trap, _) => return Err(RuntimeError::IntegerOverflow(operation, span)),
+            (OverflowMode::Wrap, BinOp::Add) => left.wrapping_add(right),
+            (OverflowMode::Wrap, BinOp::Sub) => left.wrapping_sub(right),
+            (OverflowMode::Wrap, BinOp::Mul) => left.wrapping_mul(right),
+            (OverflowMode::Wrap, BinOp::Div) => left.wrapping_div(right),
+            (OverflowMode::Saturate, BinOp::Add) => left.saturating_add(right),
+            (OverflowMode::Saturate, BinOp::Sub) => left.saturating_sub(right),
+            (OverflowMode::Saturate, BinOp::Mul) => left.saturating_mul(right),
+            (OverflowMode::Saturate, BinOp::Div) => left.saturating_div(right),
+            // o único resto que estoura é i64::MIN % -1, que na verdade é 0 nos dois modos
+            (_, BinOp::Mod) => left.wrapping_rem(right),
+            _ => unreachable!(),
+        };
+        Ok(result)
+    }
+
+    // A divisão por zero em double segue o IEEE 754 e dá infinito ou NaN, e o %, os operadores bit
+    // a bit e os deslocamentos não existem para double, assim como em C
+    fn float_arithmetic(op: BinOp, left: &Value, right: &Value, span: Span) -> Result<Value, RuntimeError> {
+        let (l, r) = match (Self::as_float(left), Self::as_float(right)) {
+            (Some(l), Some(r)) if matches!(op, BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div) => (l, r),
+            _ => return Err(Self::operand_error(op, left, right, span)),
+        };
+
+        let result = match op {
+            BinOp::Add => l + r,
+            BinOp::Sub => l - r,
+            BinOp::Mul => l * r,
+            BinOp::Div => l / r,
+            _ => unreachable!(),
+        };
+        Ok(Value::Float(result))
+    }
+
+    // <, <=, > e >= entre números (convertendo int para double se preciso) ou entre dois textos
+    // (em ordem lexicográfica). Qualquer comparação com NaN é falsa
+    fn compare(&self, op: BinOp, left: &Value, right: &Value, span: Span) -> Result<Value, RuntimeError> {
+        let ordering = match (left, right) {
+            (Value::Str(l), Value::Str(r)) => Some(l.cmp(r)),
+            _ => match (Self::integer(left), Self::integer(right)) {
+                (Some(l), Some(r)) => Some(l.cmp(&r)),
+                _ => match (Self::as_float(left), Self::as_float(right)) {
+                    (Some(l), Some(r)) => l.partial_cmp(&r),
+                    _ => return Err(Self::operand_error(op, left, right, span)),
+                },
+            },
+        };
+
+        let result = match op {
+            BinOp::Greater => ordering.is_some_and(|o| o.is_gt()),
+            BinOp::GreaterEqual => ordering.is_some_and(|o| o.is_ge()),
+            BinOp::Less => ordering.is_some_and(|o| o.is_lt()),
+            BinOp::LessEqual => ordering.is_some_and(|o| o.is_le()),
+            _ => unreachable!(),
+        };
+        Ok(Value::Bool(result))
+    }
+
+    fn operand_error(op: BinOp, left: &Value, right: &Value, span: Span) -> RuntimeError {
+        RuntimeError::TypeError(
+            format!("o operador '{}' não pode ser usado entre {} e {}", op, left.type_name(), right.type_name()),
+            span,
+        )
+    }
+
+    fn update_binop(op: UpdateOp) -> BinOp {
+        match op {
+            UpdateOp::Increment => BinOp::Add,
+            UpdateOp::Decrement => BinOp::Sub,
+        }
+    }
+
+    // Números (int, double e char) são comparados pelo valor, convertendo como numa conta
     fn values_equal(&self, left: &Value, right: &Value) -> bool {
         match (left, right) {
-            (Value::Number(a), Value::Number(b)) => a == b,
             (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
             (Value::Void, Value::Void) => true,
-            _ => false,
+            (Value::Array(a), Value::Array(b)) => Rc::ptr_eq(a, b),
+            _ => match (Self::integer(left), Self::integer(right)) {
+                (Some(a), Some(b)) => a == b,
+                _ => match (Self::as_float(left), Self::as_float(right)) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => false,
+                },
+            },
+        }
+    }
+
+    fn call_function(&mut self, name: &str, args: &[Expr], span: Span) -> Result<Value, RuntimeError> {
+        // Avalia os argumentos no ambiente de quem chamou!
+        let mut arg_values = Vec::with_capacity(args.len());
+        for arg in args {
+            arg_values.push(self.evaluate_expression(arg)?);
+        }
+
+        // As funções embutidas vêm antes das do usuário
+        if let Some(builtin) = builtins::lookup(name) {
+            return builtin(&arg_values, &mut *self.output, span);
         }
+
+        self.call_with_values(name, arg_values, span)
     }
 
-    fn call_function(&mut self, name: &str, args: &[Expr]) -> Result<Value, RuntimeError> {
+    fn call_with_values(&mut self, name: &str, arg_values: Vec<Value>, span: Span) -> Result<Value, RuntimeError> {
         // Procura a função
         let function = self.globals.get(name)
-            .ok_or_else(|| RuntimeError::UndefinedFunction(name.to_string()))?
+            .ok_or_else(|| RuntimeError::UndefinedFunction(name.to_string(), span))?
             .clone();
 
         // Verifica se o número de argumentos está correto
-        if args.len() != function.params.len() {
+        if arg_values.len() != function.params.len() {
             return Err(RuntimeError::WrongArgumentCount(
                 name.to_string(),
                 function.params.len(),
-                args.len(),
+                arg_values.len(),
+                span,
             ));
         }
 
-        // Salva o estado atual das variáveis locais
-        let saved_locals = self.locals.clone();
-
-        // Avalia os argumentos!
-        for (param, arg) in function.params.iter().zip(args.iter()) {
-            let arg_value = self.evaluate_expression(arg)?;
-            self.locals.insert(param.clone(), arg_value);
+        // A função ganha um ambiente novo, em que os parâMetros ficam no escopo mais externo
+        let mut function_env = Environment::new();
+        for (param, arg_value) in function.params.iter().zip(arg_values) {
+            let arg_value = match arg_value {
+                Value::Array(_) if param.is_array => arg_value,
+                other if param.is_array => {
+                    return Err(RuntimeError::TypeError(
+                        format!("o parâmetro '{}' espera um array, mas recebeu {}", param.name, other.type_name()),
+                        span,
+                    ));
+                }
+                other => Self::coerce(other, &param.ty, span)?,
+            };
+            if !function_env.declare(&param.name, arg_value) {
+                return Err(RuntimeError::Redeclaration(param.name.clone(), function.span));
+            }
         }
 
+        // Salva o estado atual das variáveis locais
+        let saved_locals = std::mem::replace(&mut self.locals, function_env);
+
         // Executa o corpo da função
         let result = match self.execute_statements(&function.body) {
-            Ok(value) => Ok(value),
-            Err(RuntimeError::Return(value)) => Ok(value),
+            Ok(ControlFlow::Normal(value)) => Ok(value),
+            Ok(ControlFlow::Return(value, return_span)) => Self::coerce(value, &function.return_type, return_span),
+            Ok(ControlFlow::Break | ControlFlow::Continue) => {
+                unreachable!("o parser só aceita break e continue dentro de laços e switch")
+            }
             Err(e) => Err(e),
         };
 
@@ -270,25 +1268,86 @@ impl Interpreter {
     }
 }
 
+// Onde fica o alvo de uma atribuição: uma variável (pelo nome) ou um elemento de array, seguidos
+// das posições dos campos quando o alvo está dentro de uma struct (como em pontos[0].x)
+struct Place {
+    root: PlaceRoot,
+    fields: Vec<usize>,
+}
+
+enum PlaceRoot {
+    Variable(String),
+    Element(ArrayElements, usize),
+}
+
+impl RuntimeError {
+    // O trecho do código onde o erro aconteceu
+    pub fn span(&self) -> Span {
+        match self {
+            RuntimeError::UndefinedVariable(_, _, span)
+            | RuntimeError::UndefinedFunction(_, span)
+            | RuntimeError::WrongArgumentCount(_, _, _, span)
+            | RuntimeError::DivisionByZero(span)
+            | RuntimeError::IntegerOverflow(_, span)
+            | RuntimeError::InvalidShift(_, span)
+            | RuntimeError::Redeclaration(_, span)
+            | RuntimeError::IndexOutOfBounds { span, .. }
+            | RuntimeError::InvalidArray(_, span)
+            | RuntimeError::UndefinedStruct(_, span)
+            | RuntimeError::UndefinedField(_, _, span)
+            | RuntimeError::AssignToConstant(_, span)
+            | RuntimeError::BuiltinError(_, _, span)
+            | RuntimeError::TypeError(_, span) => *span,
+        }
+    }
+}
+
 //Basicamente, gestão de erros e comportamentos anôMalos
 impl std::fmt::Display for RuntimeError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            RuntimeError::UndefinedVariable(name) => {
+            RuntimeError::UndefinedVariable(name, _, _) => {
                 write!(f, "Erro: Variável '{}' não definida", name)
             }
-            RuntimeError::UndefinedFunction(name) => {
+            RuntimeError::UndefinedFunction(name, _) => {
                 write!(f, "Erro: Função '{}' não definida", name)
             }
-            RuntimeError::WrongArgumentCount(name, expected, actual) => {
+            RuntimeError::WrongArgumentCount(name, expected, actual, _) => {
                 write!(f, "Erro: Função '{}' espera {} argumentos, mas recebeu {}",
                        name, expected, actual)
             }
-            RuntimeError::DivisionByZero => {
+            RuntimeError::DivisionByZero(_) => {
                 write!(f, "Erro: Divisão por zero")
             }
-            RuntimeError::Return(_) => {
-                write!(f, "Erro interno: Return não capturado")
+            RuntimeError::IntegerOverflow(operation, _) => {
+                write!(f, "Erro: Estouro de inteiro: o resultado de {} não cabe em 64 bits", operation)
+            }
+            RuntimeError::InvalidShift(bits, _) => {
+                write!(f, "Erro: Deslocamento de {} bits, mas só é possível deslocar de 0 a 63", bits)
+            }
+            RuntimeError::Redeclaration(name, _) => {
+                write!(f, "Erro: Variável '{}' já declarada neste escopo", name)
+            }
+            RuntimeError::IndexOutOfBounds { index, len, .. } => {
+                write!(f, "Erro: Índice {} fora dos limites do array de tamanho {}", index, len)
+            }
+            RuntimeError::InvalidArray(message, _) => {
+                write!(f, "Erro: Array inválido: {}", message)
+            }
+            RuntimeError::UndefinedStruct(name, _) => {
+                write!(f, "Erro: Struct '{}' não definida", name)
+            }
+            RuntimeError::UndefinedField(name, field, _) => {
+                write!(f, "Erro: A struct '{}' não tem o campo '{}'", name, field)
+            }
+            RuntimeError::AssignToConstant(name, _) => {
+                write!(f, "Erro: '{}' é uma constante de enum e não pode ser alterada", name)
+            }
+            RuntimeError::BuiltinError(name, message, _) => {
+                write!(f, "Erro: Na função '{}': {}", name, message)
+            }
+            RuntimeError::TypeError(message, _) => {
+                write!(f, "Erro: Tipo incompatível: {}", message)
             }
         }
     }
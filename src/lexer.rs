@@ -2,28 +2,53 @@
 // deles
 use std::str::Chars;
 use std::iter::Peekable;
+use crate::span::Span;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     // Literais
     Number(i64),
+    Decimal(f64),
     Ident(String),
+    Str(String),
+    Character(char),
     True,
     False,
     // Palavras-chave daquilo que foi implementado neste programa já
     Int,
     Bool,
+    String,
+    Double,
+    Float,
+    Char,
+    Struct,
+    Enum,
     Return,
     If,
     Else,
     While,
+    Do,
     For,
+    Switch,
+    Case,
+    Default,
+    Break,
+    Continue,
     // Operadores aritméticos
     Mais,
     Menos,
     Multiplica,
     Divide,
+    Resto,        // %
     Assign, // A atribuição de valor
+    // Atribuições compostas e incremento/decremento
+    MaisIgual,       // +=
+    MenosIgual,      // -=
+    MultiplicaIgual, // *=
+    DivideIgual,     // /=
+    RestoIgual,      // %=
+    Incremento,      // ++
+    Decremento,      // --
     // Operadores de comparação
     Equal,
     NotEqual,
@@ -35,19 +60,135 @@ pub enum Token {
     And,          // &&
     Or,           // ||
     Not,          // !
+    // Operadores bit a bit e de deslocamento
+    BitAnd,       // &
+    BitOr,        // |
+    BitXor,       // ^
+    BitNot,       // ~
+    ShiftLeft,    // <<
+    ShiftRight,   // >>
     // Pontuação e outros sinais gráficos e afins
     AbrePar,      // (
     FechaPar,     // )
     AbreChave,    // {
     FechaChave,   // }
+    AbreColchete, // [
+    FechaColchete, // ]
     PontoEVirgula,
     Virgula,
+    Ponto,        // . (acesso a campo de struct)
+    DoisPontos,   // :
+    Interrogacao, // ?
+}
+
+// Como o token aparece em mensagens de erro, próximo de como ele foi escrito no código
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Token::Number(n) => return write!(f, "número {}", n),
+            Token::Decimal(n) => return write!(f, "número {}", n),
+            Token::Ident(name) => return write!(f, "identificador '{}'", name),
+            Token::Str(text) => return write!(f, "texto \"{}\"", text),
+            Token::Character(c) => return write!(f, "caractere {:?}", c),
+            Token::True => "true",
+            Token::False => "false",
+            Token::Int => "int",
+            Token::Bool => "bool",
+            Token::String => "string",
+            Token::Double => "double",
+            Token::Float => "float",
+            Token::Char => "char",
+            Token::Struct => "struct",
+            Token::Enum => "enum",
+            Token::Return => "return",
+            Token::If => "if",
+            Token::Else => "else",
+            Token::While => "while",
+            Token::Do => "do",
+            Token::For => "for",
+            Token::Switch => "switch",
+            Token::Case => "case",
+            Token::Default => "default",
+            Token::Break => "break",
+            Token::Continue => "continue",
+            Token::Mais => "+",
+            Token::Menos => "-",
+            Token::Multiplica => "*",
+            Token::Divide => "/",
+            Token::Resto => "%",
+            Token::Assign => "=",
+            Token::MaisIgual => "+=",
+            Token::MenosIgual => "-=",
+            Token::MultiplicaIgual => "*=",
+            Token::DivideIgual => "/=",
+            Token::RestoIgual => "%=",
+            Token::Incremento => "++",
+            Token::Decremento => "--",
+            Token::Equal => "==",
+            Token::NotEqual => "!=",
+            Token::Less => "<",
+            Token::Greater => ">",
+            Token::LessEqual => "<=",
+            Token::GreaterEqual => ">=",
+            Token::And => "&&",
+            Token::Or => "||",
+            Token::Not => "!",
+            Token::BitAnd => "&",
+            Token::BitOr => "|",
+            Token::BitXor => "^",
+            Token::BitNot => "~",
+            Token::ShiftLeft => "<<",
+            Token::ShiftRight => ">>",
+            Token::AbrePar => "(",
+            Token::FechaPar => ")",
+            Token::AbreChave => "{",
+            Token::FechaChave => "}",
+            Token::AbreColchete => "[",
+            Token::FechaColchete => "]",
+            Token::PontoEVirgula => ";",
+            Token::Virgula => ",",
+            Token::Ponto => ".",
+            Token::DoisPontos => ":",
+            Token::Interrogacao => "?",
+        };
+        write!(f, "'{}'", text)
+    }
+}
+
+// O token junto do trecho do código de onde ele saiu
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
+
+// Os tipos de erro que o lexer consegue detectar
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexErrorKind {
+    UnexpectedChar(char),
+    UnterminatedComment,
+    UnterminatedString,
+    InvalidEscape(String), // a sequência como foi escrita, como \q
+    InvalidCharLiteral,
+    IntegerOverflow(String), // o literal como foi escrito
+}
+
+// Um erro léxico e o trecho do código onde ele aconteceu
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub span: Span,
 }
 
 pub struct Lexer<'a> {
     chars: Peekable<Chars<'a>>,
     #[allow(dead_code)]
     buffer: String,
+    // posição atual: deslocamento em bytes, linha e coluna (a partir de 1)
+    offset: usize,
+    line: usize,
+    column: usize,
+    errors: Vec<LexError>,
 }
 
 impl<'a> Lexer<'a> {
@@ -55,19 +196,66 @@ impl<'a> Lexer<'a> {
         Lexer {
             chars: input.chars().peekable(),
             buffer: String::new(),
+            offset: 0,
+            line: 1,
+            column: 1,
+            errors: Vec::new(),
         }
     }
 
-    pub fn tokenizador(&mut self) -> Vec<Token> {
+    // Quebra a entrada inteira em tokens. Um erro não interrompe a análise: o lexer registra o
+    // erro, segue em frente e no final devolve todos os erros encontrados
+    pub fn tokenizador(&mut self) -> Result<Vec<SpannedToken>, Vec<LexError>> {
         let mut tokens = Vec::new();
         while let Some(token) = self.proximo_token() {
             tokens.push(token);
         }
-        tokens
+
+        if self.errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
     }
 
-    fn proximo_token(&mut self) -> Option<Token> {
-        while let Some(c) = self.chars.next() {
+    // Consome um caractere, atualizando a posição atual
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    // O span que vai de `start` até a posição atual
+    fn span_from(&self, start: (usize, usize, usize)) -> Span {
+        let (offset, line, column) = start;
+        Span::new(offset, self.offset, line, column)
+    }
+
+    // Monta o token com o span que vai de `start` até a posição atual
+    fn spanned(&self, token: Token, start: (usize, usize, usize)) -> SpannedToken {
+        SpannedToken {
+            token,
+            span: self.span_from(start),
+        }
+    }
+
+    // Registra um erro que vai de `start` até a posição atual
+    fn error(&mut self, kind: LexErrorKind, start: (usize, usize, usize)) {
+        let span = self.span_from(start);
+        self.errors.push(LexError { kind, span });
+    }
+
+    fn proximo_token(&mut self) -> Option<SpannedToken> {
+        loop {
+            let start = (self.offset, self.line, self.column);
+            let c = self.bump()?;
+
             // Pula espaços em branco
             if c.is_whitespace() {
                 continue;
@@ -78,40 +266,70 @@ impl<'a> Lexer<'a> {
                 if let Some(&next_char) = self.chars.peek() {
                     if next_char == '/' {
                         // Comentário de linha: // até o fim da linha
-                        self.chars.next(); // consome o segundo '/'
+                        self.bump(); // consome o segundo '/'
                         self.skip_line_comment();
                         continue;
                     } else if next_char == '*' {
                         // Comentário de bloco: /* ... */
-                        self.chars.next(); // consome o '*'
-                        if self.skip_block_comment() {
-                            continue;
-                        } else {
+                        self.bump(); // consome o '*'
+                        if !self.skip_block_comment() {
                             // Se não conseguiu fechar o comentário, erro
-                            panic!("Comentário de bloco não fechado");
+                            self.error(LexErrorKind::UnterminatedComment, start);
                         }
+                        continue;
+                    } else if next_char == '=' {
+                        self.bump(); // consome o '='
+                        return Some(self.spanned(Token::DivideIgual, start));
                     } else {
                         // É apenas um operador de divisão
-                        return Some(Token::Divide);
+                        return Some(self.spanned(Token::Divide, start));
                     }
                 } else {
                     // É apenas um operador de divisão no final do arquivo
-                    return Some(Token::Divide);
+                    return Some(self.spanned(Token::Divide, start));
                 }
             }
 
             // Números
             if c.is_ascii_digit() {
-                let mut number = c.to_digit(10).unwrap() as i64;
-                while let Some(&next) = self.chars.peek() {
-                    if next.is_ascii_digit() {
-                        self.chars.next();
-                        number = number * 10 + next.to_digit(10).unwrap() as i64;
-                    } else {
-                        break;
+                let mut literal = c.to_string();
+                self.read_digits(&mut literal);
+
+                // parte decimal (1.5) e expoente (1e-3, 2.5E+4) fazem do literal um double. O '.'
+                // e o 'e' só contam se vierem seguidos de dígitos
+                let mut is_decimal = false;
+                if self.chars.peek() == Some(&'.') && self.digit_after(1) {
+                    is_decimal = true;
+                    literal.push('.');
+                    self.bump();
+                    self.read_digits(&mut literal);
+                }
+                if matches!(self.chars.peek(), Some('e' | 'E')) {
+                    let sign = matches!(self.peek_nth(1), Some('+' | '-'));
+                    if self.digit_after(if sign { 2 } else { 1 }) {
+                        is_decimal = true;
+                        literal.push('e');
+                        self.bump();
+                        if sign {
+                            literal.push(self.bump().unwrap());
+                        }
+                        self.read_digits(&mut literal);
+                    }
+                }
+
+                if is_decimal {
+                    let number = literal.parse::<f64>().expect("literal decimal já validado");
+                    return Some(self.spanned(Token::Decimal(number), start));
+                }
+
+                // um literal maior que i64::MAX não cabe num int
+                match literal.parse::<i64>() {
+                    Ok(number) => return Some(self.spanned(Token::Number(number), start)),
+                    Err(_) => {
+                        self.error(LexErrorKind::IntegerOverflow(literal), start);
+                        continue;
                     }
                 }
-                return Some(Token::Number(number));
             }
 
             // Identificadores e palavras-chave
@@ -119,40 +337,115 @@ impl<'a> Lexer<'a> {
                 let mut ident = c.to_string();
                 while let Some(&next) = self.chars.peek() {
                     if next.is_ascii_alphanumeric() || next == '_' {
-                        self.chars.next();
+                        self.bump();
                         ident.push(next);
                     } else {
                         break;
                     }
                 }
-                return Some(match ident.as_str() {
+                let token = match ident.as_str() {
                     "int" => Token::Int,
                     "bool" => Token::Bool,
+                    "string" => Token::String,
+                    "double" => Token::Double,
+                    "float" => Token::Float,
+                    "char" => Token::Char,
+                    "struct" => Token::Struct,
+                    "enum" => Token::Enum,
                     "return" => Token::Return,
                     "if" => Token::If,
                     "else" => Token::Else,
                     "while" => Token::While,
+                    "do" => Token::Do,
                     "for" => Token::For,
+                    "switch" => Token::Switch,
+                    "case" => Token::Case,
+                    "default" => Token::Default,
+                    "break" => Token::Break,
+                    "continue" => Token::Continue,
                     "true" => Token::True,
                     "false" => Token::False,
                     _ => Token::Ident(ident),
-                });
+                };
+                return Some(self.spanned(token, start));
+            }
+
+            // Textos entre aspas duplas, que não podem atravessar linhas
+            if c == '"' {
+                match self.read_string() {
+                    Ok(text) => return Some(self.spanned(Token::Str(text), start)),
+                    Err(kind) => {
+                        self.error(kind, start);
+                        continue;
+                    }
+                }
+            }
+
+            // Caracteres entre aspas simples, como 'a' ou '\n'
+            if c == '\'' {
+                match self.read_char_literal() {
+                    Ok(character) => return Some(self.spanned(Token::Character(character), start)),
+                    Err(kind) => {
+                        self.error(kind, start);
+                        continue;
+                    }
+                }
             }
 
             // Outros tokens
-            return Some(match c {
-                '+' => Token::Mais,
-                '-' => Token::Menos,
-                '*' => Token::Multiplica,
+            let token = match c {
+                '+' => match self.chars.peek() {
+                    Some(&'+') => {
+                        self.bump(); // consome o segundo '+'
+                        Token::Incremento
+                    }
+                    Some(&'=') => {
+                        self.bump(); // consome o '='
+                        Token::MaisIgual
+                    }
+                    _ => Token::Mais,
+                },
+                '-' => match self.chars.peek() {
+                    Some(&'-') => {
+                        self.bump(); // consome o segundo '-'
+                        Token::Decremento
+                    }
+                    Some(&'=') => {
+                        self.bump(); // consome o '='
+                        Token::MenosIgual
+                    }
+                    _ => Token::Menos,
+                },
+                '*' => {
+                    if let Some(&'=') = self.chars.peek() {
+                        self.bump(); // consome o '='
+                        Token::MultiplicaIgual
+                    } else {
+                        Token::Multiplica
+                    }
+                }
+                '%' => {
+                    if let Some(&'=') = self.chars.peek() {
+                        self.bump(); // consome o '='
+                        Token::RestoIgual
+                    } else {
+                        Token::Resto
+                    }
+                }
                 '(' => Token::AbrePar,
                 ')' => Token::FechaPar,
                 '{' => Token::AbreChave,
                 '}' => Token::FechaChave,
+                '[' => Token::AbreColchete,
+                ']' => Token::FechaColchete,
                 ';' => Token::PontoEVirgula,
                 ',' => Token::Virgula,
+                '.' => Token::Ponto,
+                ':' => Token::DoisPontos,
+                '?' => Token::Interrogacao,
                 '=' => {
                     if let Some(&'=') = self.chars.peek() {
-                        self.chars.next(); // consome o segundo '='
+                        self.bump(); // consome o segundo '='
                         Token::Equal
                     } else {
                         Token::Assign
@@ -160,53 +453,193 @@ impl<'a> Lexer<'a> {
                 }
                 '!' => {
                     if let Some(&'=') = self.chars.peek() {
-                        self.chars.next(); // consome o '='
+                        self.bump(); // consome o '='
                         Token::NotEqual
                     } else {
                         Token::Not
                     }
                 }
-                '<' => {
-                    if let Some(&'=') = self.chars.peek() {
-                        self.chars.next(); // consome o '='
+                '<' => match self.chars.peek() {
+                    Some(&'=') => {
+                        self.bump(); // consome o '='
                         Token::LessEqual
-                    } else {
-                        Token::Less
                     }
-                }
-                '>' => {
-                    if let Some(&'=') = self.chars.peek() {
-                        self.chars.next(); // consome o '='
+                    Some(&'<') => {
+                        self.bump(); // consome o segundo '<'
+                        Token::ShiftLeft
+                    }
+                    _ => Token::Less,
+                },
+                '>' => match self.chars.peek() {
+                    Some(&'=') => {
+                        self.bump(); // consome o '='
                         Token::GreaterEqual
-                    } else {
-                        Token::Greater
                     }
-                }
+                    Some(&'>') => {
+                        self.bump(); // consome o segundo '>'
+                        Token::ShiftRight
+                    }
+                    _ => Token::Greater,
+                },
                 '&' => {
                     if let Some(&'&') = self.chars.peek() {
-                        self.chars.next(); // consome o segundo '&'
+                        self.bump(); // consome o segundo '&'
                         Token::And
                     } else {
-                        continue; // ignora '&' sozinho, por enquanto
+                        Token::BitAnd
                     }
                 }
                 '|' => {
                     if let Some(&'|') = self.chars.peek() {
-                        self.chars.next(); // consome o segundo '|'
+                        self.bump(); // consome o segundo '|'
                         Token::Or
                     } else {
-                        continue; // não trata '|' sozinho, por enquanto
+                        Token::BitOr
+                    }
+                }
+                '^' => Token::BitXor,
+                '~' => Token::BitNot,
+                _ => {
+                    // caracteres desconhecidos e/ou não configurados ainda
+                    self.error(LexErrorKind::UnexpectedChar(c), start);
+                    continue;
+                }
+            };
+            return Some(self.spanned(token, start));
+        }
+    }
+
+    // Consome os dígitos seguidos, acrescentando-os ao literal
+    fn read_digits(&mut self, literal: &mut String) {
+        while let Some(&next) = self.chars.peek() {
+            if next.is_ascii_digit() {
+                self.bump();
+                literal.push(next);
+            } else {
+                break;
+            }
+        }
+    }
+
+    // O caractere `n` posições à frente, sem consumir nada
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.chars.clone().nth(n)
+    }
+
+    // Verifica se o caractere `n` posições à frente é um dígito
+    fn digit_after(&self, n: usize) -> bool {
+        self.peek_nth(n).is_some_and(|c| c.is_ascii_digit())
+    }
+
+    // Lê o conteúdo de um texto até as aspas de fechamento, já trocando as sequências de escape
+    // pelos caracteres que representam. Uma sequência inválida não interrompe a leitura, para que
+    // o erro cubra o texto inteiro e a análise continue depois das aspas
+    fn read_string(&mut self) -> Result<String, LexErrorKind> {
+        let mut text = String::new();
+        let mut invalid_escape = None;
+        while let Some(&c) = self.chars.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.bump();
+            match c {
+                '"' => return match invalid_escape {
+                    Some(escape) => Err(LexErrorKind::InvalidEscape(escape)),
+                    None => Ok(text),
+                },
+                '\\' => match self.read_escape() {
+                    Ok(escaped) => text.push(escaped),
+                    Err(escape) => {
+                        invalid_escape.get_or_insert(escape);
+                    }
+                },
+                _ => text.push(c),
+            }
+        }
+        Err(LexErrorKind::UnterminatedString)
+    }
+
+    // Lê um literal de caractere depois da aspa simples de abertura: um único caractere (ou
+    // sequência de escape) e a aspa de fechamento
+    fn read_char_literal(&mut self) -> Result<char, LexErrorKind> {
+        let character = match self.chars.peek() {
+            Some(&'\\') => {
+                self.bump();
+                self.read_escape().map_err(LexErrorKind::InvalidEscape)
+            }
+            Some(&c) if c != '\'' && c != '\n' => {
+                self.bump();
+                Ok(c)
+            }
+            _ => Err(LexErrorKind::InvalidCharLiteral),
+        };
+
+        if self.chars.peek() == Some(&'\'') {
+            self.bump();
+            return character;
+        }
+
+        // Não fechou logo depois de um caractere: descarta o resto do literal, até a aspa ou o fim
+        // da linha, para que o erro cubra o literal inteiro
+        while let Some(&c) = self.chars.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.bump();
+            if c == '\'' {
+                break;
+            }
+        }
+        Err(character.err().unwrap_or(LexErrorKind::InvalidCharLiteral))
+    }
+
+    // Lê o que vem depois de uma '\': \n, \t, \", \', \\ ou \u{código em hexadecimal}. Em caso
+    // de erro devolve a sequência como foi escrita
+    fn read_escape(&mut self) -> Result<char, String> {
+        let c = match self.chars.peek() {
+            Some(&c) if c != '\n' => c,
+            _ => return Err("\\".to_string()),
+        };
+        self.bump();
+        match c {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            '"' => Ok('"'),
+            '\'' => Ok('\''),
+            '\\' => Ok('\\'),
+            'u' => {
+                let mut escape = "\\u".to_string();
+                if self.chars.peek() != Some(&'{') {
+                    return Err(escape);
+                }
+                self.bump();
+                escape.push('{');
+                let mut digits = String::new();
+                while let Some(&d) = self.chars.peek() {
+                    if !d.is_ascii_hexdigit() {
+                        break;
                     }
+                    self.bump();
+                    digits.push(d);
                 }
-                _ => continue, // ignora caracteres desconhecidos e/ou não configurados ainda
-            });
+                escape.push_str(&digits);
+                if self.chars.peek() != Some(&'}') {
+                    return Err(escape);
+                }
+                self.bump();
+                escape.push('}');
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(escape)
+            }
+            other => Err(format!("\\{}", other)),
         }
-        None
     }
 
     fn skip_line_comment(&mut self) {
         // Consome caracteres até encontrar uma nova linha ou o final do arquivo
-        while let Some(c) = self.chars.next() {
+        while let Some(c) = self.bump() {
             if c == '\n' {
                 break;
             }
@@ -215,11 +648,11 @@ impl<'a> Lexer<'a> {
 
     fn skip_block_comment(&mut self) -> bool {
         // Consome caracteres até encontrar */ ou o final do arquivo
-        while let Some(c) = self.chars.next() {
+        while let Some(c) = self.bump() {
             if c == '*' {
                 if let Some(&next_char) = self.chars.peek() {
                     if next_char == '/' {
-                        self.chars.next(); // consome o '/'
+                        self.bump(); // consome o '/'
                         return true; // Comentário fechado corretamente!
                     }
                 }
@@ -228,3 +661,24 @@ impl<'a> Lexer<'a> {
         false // Chegou ao fim do arquivo sem fechar o comentário
     }
 }
+
+impl std::fmt::Display for LexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            LexErrorKind::UnexpectedChar(c) => write!(f, "Erro léxico: caractere inesperado '{}'", c),
+            LexErrorKind::UnterminatedComment => write!(f, "Erro léxico: comentário de bloco não fechado"),
+            LexErrorKind::UnterminatedString => write!(f, "Erro léxico: texto sem as aspas de fechamento"),
+            LexErrorKind::InvalidCharLiteral => {
+                write!(f, "Erro léxico: literal de caractere inválido, use um único caractere entre aspas simples")
+            }
+            LexErrorKind::InvalidEscape(escape) => {
+                write!(f, "Erro léxico: sequência de escape inválida '{}' no texto", escape)
+            }
+            LexErrorKind::IntegerOverflow(literal) => {
+                write!(f, "Erro léxico: o literal inteiro {} não cabe num int", literal)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LexError {}
@@ -1,10 +1,20 @@
+pub mod span;
 pub mod lexer;
 pub mod parser;
 pub mod ast;
 pub mod interpreter;
+pub mod env;
+pub mod builtins;
+pub mod diagnostics;
+pub mod repl;
 
 //exporta de novo os tipos principais para facilitar o uso em outros arquivos e afins
-pub use lexer::{Lexer, Token};
-pub use parser::Parser;
-pub use ast::{Stmt, Expr, Function, BinOp, LogicalOp, UnaryOp};
-pub use interpreter::{Interpreter, Value, RuntimeError};
\ No newline at end of file
+pub use span::Span;
+pub use lexer::{Lexer, Token, SpannedToken, LexError, LexErrorKind};
+pub use parser::{Parser, ParseError};
+pub use ast::{Program, Item, ReplInput, Stmt, StmtKind, SwitchCase, Initializer, Expr, ExprKind, Function, Param, StructDef, Field, EnumDef, Type, BinOp, LogicalOp, UnaryOp, UpdateOp};
+pub use interpreter::{Interpreter, OverflowMode, Value, StructValue, RuntimeError};
+pub use env::Environment;
+pub use builtins::SharedOutput;
+pub use diagnostics::Diagnostic;
+pub use repl::Repl;
\ No newline at end of file
@@ -1,51 +1,334 @@
-use mini_interpretador::{Lexer, Parser, Interpreter, Value};
+use std::io::{BufRead, IsTerminal, Read, Write};
+use mini_interpretador::{Lexer, Parser, Interpreter, OverflowMode, Value, Diagnostic, SpannedToken, Program, Repl};
+
+const USO: &str = "\
+Uso: mini_interpretador [opções] <comando> <arquivo.mc> [args...]
+
+Comandos:
+  run <arquivo> [args...]   executa o programa, passando os args para os parâmetros da main
+  tokens <arquivo>          mostra os tokens da análise léxica
+  ast <arquivo>             mostra a AST da análise sintática
+  check <arquivo>           só verifica se há erros léxicos ou sintáticos
+  repl                      abre o modo interativo (use :help dentro dele)
+
+Use '-' no lugar do arquivo para ler o programa da entrada padrão.
+
+Opções:
+  -q, --quiet               mostra só o resultado do programa
+  -v, --verbose             mostra o relatório completo: tokens, AST e resultado
+  --overflow=<modo>         o que fazer quando uma conta inteira estoura: trap (erro, o padrão),
+                            wrap (dá a volta) ou saturate (fica no maior/menor valor)
+  -h, --help                mostra esta ajuda
+
+Código de saída:
+  o valor devolvido pela main, como em C (n & 0xff); em caso de falha:
+  64 uso inválido, 65 erro léxico, 66 erro sintático, 70 erro de execução, 74 erro de leitura
+";
+
+//códigos de saída do processo quando algo dá errado, seguindo o sysexits.h quando possível
+const SAIDA_USO: i32 = 64;
+const SAIDA_ERRO_LEXICO: i32 = 65;
+const SAIDA_ERRO_SINTATICO: i32 = 66;
+const SAIDA_ERRO_EXECUCAO: i32 = 70;
+const SAIDA_ERRO_LEITURA: i32 = 74;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comando {
+    Run,
+    Tokens,
+    Ast,
+    Check,
+    Repl,
+}
+
+//o que foi pedido na linha de comando
+struct Opcoes {
+    comando: Comando,
+    arquivo: String,
+    args: Vec<String>,
+    quiet: bool,
+    verbose: bool,
+    overflow: OverflowMode,
+}
+
+//o programa lido, junto do nome usado nas mensagens de erro
+struct Fonte {
+    nome: String,
+    texto: String,
+}
+
 fn main() {
-    //Caso queira que outro programa seja lido, o path terá de ser mudado aqui
-    //Por enquanto, o default/padrão será o programa.mc mesmo, que inclusive está nesta mesma pasta
-    let input = std::fs::read_to_string("programa.mc").expect("Erro ao ler arquivo");
-    println!("Este programa quebra seu código em pequenas partes, analisa-o léxica e
-sintaticamente e o interpreta!");
-    println!("Hora de rodar o seu programa. Ihuul! Vamos dividi-lo em 3:\n");
-    //etapa 1: análise léxica
-    let mut lexer = Lexer::nova_instancia(&input);
-    let tokens = lexer.tokenizador();
-    println!("Estes são os tokens, que representam a análise léxica do seu programa:");
-    for token in &tokens {
-        println!("{:?}", token);
+    let opcoes = match ler_opcoes(std::env::args().skip(1)) {
+        Ok(Some(opcoes)) => opcoes,
+        Ok(None) => {
+            print!("{}", USO);
+            return;
+        }
+        Err(mensagem) => {
+            eprintln!("erro: {}\n\n{}", mensagem, USO);
+            std::process::exit(SAIDA_USO);
+        }
+    };
+
+    if opcoes.comando == Comando::Repl {
+        if let Err(e) = rodar_repl(opcoes.overflow) {
+            eprintln!("erro: {}", e);
+            std::process::exit(SAIDA_ERRO_LEITURA);
+        }
+        return;
+    }
+
+    let fonte = match ler_fonte(&opcoes.arquivo) {
+        Ok(fonte) => fonte,
+        Err(e) => {
+            eprintln!("erro: não foi possível ler '{}': {}", opcoes.arquivo, e);
+            std::process::exit(SAIDA_ERRO_LEITURA);
+        }
+    };
+
+    if let Err(codigo) = executar(&opcoes, &fonte) {
+        std::process::exit(codigo);
+    }
+}
+
+//lê os argumentos; devolve None quando foi pedida a ajuda
+fn ler_opcoes(args: impl Iterator<Item = String>) -> Result<Option<Opcoes>, String> {
+    let mut comando = None;
+    let mut arquivo = None;
+    let mut resto = Vec::new();
+    let mut quiet = false;
+    let mut verbose = false;
+    let mut overflow = OverflowMode::default();
+
+    for arg in args {
+        //depois do arquivo, tudo é argumento do programa (inclusive números negativos)
+        if arquivo.is_some() {
+            resto.push(arg);
+            continue;
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-q" | "--quiet" => quiet = true,
+            "-v" | "--verbose" => verbose = true,
+            "-" => arquivo = Some(arg),
+            opcao if opcao.starts_with("--overflow=") => {
+                overflow = match &opcao["--overflow=".len()..] {
+                    "trap" => OverflowMode::Trap,
+                    "wrap" => OverflowMode::Wrap,
+                    "saturate" => OverflowMode::Saturate,
+                    outro => return Err(format!("modo de estouro desconhecido '{}' (use trap, wrap ou saturate)", outro)),
+                };
+            }
+            opcao if opcao.starts_with('-') => return Err(format!("opção desconhecida '{}'", opcao)),
+            _ if comando.is_none() => {
+                comando = Some(match arg.as_str() {
+                    "run" => Comando::Run,
+                    "tokens" => Comando::Tokens,
+                    "ast" => Comando::Ast,
+                    "check" => Comando::Check,
+                    "repl" => Comando::Repl,
+                    outro => return Err(format!("comando desconhecido '{}'", outro)),
+                });
+            }
+            _ => arquivo = Some(arg),
+        }
+    }
+
+    let comando = comando.ok_or("nenhum comando informado")?;
+    if comando == Comando::Repl {
+        if arquivo.is_some() {
+            return Err("o comando repl não recebe arquivo (use :load dentro dele)".to_string());
+        }
+        return Ok(Some(Opcoes { comando, arquivo: String::new(), args: resto, quiet, verbose, overflow }));
+    }
+    let arquivo = arquivo.ok_or("nenhum arquivo informado")?;
+    if comando != Comando::Run && !resto.is_empty() {
+        return Err("só o comando run aceita argumentos para o programa".to_string());
+    }
+    if quiet && verbose {
+        return Err("--quiet e --verbose não podem ser usados juntos".to_string());
+    }
+
+    Ok(Some(Opcoes { comando, arquivo, args: resto, quiet, verbose, overflow }))
+}
+
+//lê o programa do arquivo, ou da entrada padrão quando o arquivo é '-'
+fn ler_fonte(arquivo: &str) -> std::io::Result<Fonte> {
+    if arquivo == "-" {
+        let mut texto = String::new();
+        std::io::stdin().read_to_string(&mut texto)?;
+        Ok(Fonte { nome: "<stdin>".to_string(), texto })
+    } else {
+        let texto = std::fs::read_to_string(arquivo)?;
+        Ok(Fonte { nome: arquivo.to_string(), texto })
+    }
+}
+
+//o modo interativo: lê linhas da entrada padrão até o fim dela ou até um :quit
+fn rodar_repl(overflow: OverflowMode) -> std::io::Result<()> {
+    let mut repl = Repl::new();
+    repl.set_overflow_mode(overflow);
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+    let interativo = stdin.is_terminal();
+
+    if interativo {
+        println!("mini_interpretador {} - digite :help para ver os comandos", env!("CARGO_PKG_VERSION"));
     }
-    println!();
 
-    //etapa 2: análise sintática
+    let mut linhas = stdin.lock().lines();
+    loop {
+        if interativo {
+            print!("{}", repl.prompt());
+            stdout.flush()?;
+        }
+
+        let linha = match linhas.next() {
+            Some(linha) => linha?,
+            None => break,
+        };
+        if !repl.feed_line(&linha, &mut stdout)? {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+//as cores só são usadas quando a saída de erro é um terminal e NO_COLOR não está definido
+fn usar_cores() -> bool {
+    std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+fn mostrar_erro(fonte: &Fonte, diagnostico: Diagnostic) {
+    eprint!("{}", diagnostico.render(&fonte.nome, &fonte.texto, usar_cores()));
+}
+
+//etapa 1: análise léxica
+fn tokenizar(fonte: &Fonte) -> Result<Vec<SpannedToken>, i32> {
+    let mut lexer = Lexer::nova_instancia(&fonte.texto);
+    lexer.tokenizador().map_err(|erros| {
+        for erro in &erros {
+            mostrar_erro(fonte, Diagnostic::from(erro));
+        }
+        SAIDA_ERRO_LEXICO
+    })
+}
+
+//etapa 2: análise sintática
+fn analisar(fonte: &Fonte, tokens: Vec<SpannedToken>) -> Result<Program, i32> {
     let mut parser = Parser::new(tokens);
-    let (funcoes, main_body) = parser.parse();
-    println!("\nAs funções definidas pela análise sintática são as seguintes:");
-    for f in &funcoes {
-        println!("{:#?}", f);
-    }
-    println!();
-
-    println!("\nO corpo da main é este:");
-    for stmt in &main_body {
-        println!("{:#?}", stmt);
-    }
-    println!();
-
-    //etapa 3: interpretação
-    let mut interpreter = Interpreter::new();
-    println!("Hora de executar o programa com a ajuda do interpretador!");
-    match interpreter.interpret(funcoes, main_body) {
-        Ok(result) => {
-            println!("Programa executado com sucesso!");
-            match result {
-                Value::Number(n) => println!("Resultado: {}", n),
-                Value::Bool(b) => println!("Resultado: {}", b),
-                Value::Void => println!("Resultado: void"),
+    parser.parse().map_err(|erros| {
+        for erro in &erros {
+            mostrar_erro(fonte, Diagnostic::from(erro));
+        }
+        SAIDA_ERRO_SINTATICO
+    })
+}
+
+//converte os argumentos da linha de comando em valores para a main
+fn converter_argumentos(args: &[String]) -> Result<Vec<Value>, String> {
+    args.iter()
+        .map(|arg| match arg.as_str() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => arg
+                .parse::<i64>()
+                .map(Value::Number)
+                .or_else(|_| arg.parse::<f64>().map(Value::Float))
+                .map_err(|_| format!("argumento inválido '{}': use um número, true ou false", arg)),
+        })
+        .collect()
+}
+
+//o código de saída do processo a partir do valor devolvido pela main. Como em C, só os 8 bits
+//mais baixos chegam ao shell, então -1 vira 255 e 256 vira 0
+fn codigo_de_saida(valor: &Value) -> i32 {
+    match valor {
+        Value::Number(n) => (*n & 0xff) as i32,
+        Value::Float(f) => (*f as i64 & 0xff) as i32,
+        Value::Char(c) => (*c as u32 & 0xff) as i32,
+        Value::Bool(b) => i32::from(*b),
+        Value::Str(_) | Value::Array(_) | Value::Struct(_) | Value::Void => 0,
+    }
+}
+
+fn executar(opcoes: &Opcoes, fonte: &Fonte) -> Result<(), i32> {
+    let tokens = tokenizar(fonte)?;
+
+    if opcoes.verbose && opcoes.comando == Comando::Run {
+        println!("Este programa quebra seu código em pequenas partes, analisa-o léxica e
+sintaticamente e o interpreta!");
+        println!("Hora de rodar o seu programa. Ihuul! Vamos dividi-lo em 3:\n");
+        println!("Estes são os tokens, que representam a análise léxica do seu programa:");
+        for token in &tokens {
+            println!("{:?} ({})", token.token, token.span);
+        }
+        println!();
+    }
+
+    if opcoes.comando == Comando::Tokens {
+        for token in &tokens {
+            println!("{}:{}\t{}", token.span.line, token.span.column, token.token);
+        }
+        return Ok(());
+    }
+
+    let programa = analisar(fonte, tokens)?;
+
+    if opcoes.verbose && opcoes.comando == Comando::Run {
+        println!("\nOs itens definidos pela análise sintática são os seguintes:");
+        for item in &programa.items {
+            println!("{:#?}", item);
+        }
+        println!();
+        println!("Hora de executar o programa com a ajuda do interpretador!");
+    }
+
+    match opcoes.comando {
+        Comando::Ast => {
+            for item in &programa.items {
+                println!("{:#?}", item);
             }
+            Ok(())
         }
-        //caso haja erro
-        Err(e) => {
-            eprintln!("Erro durante a execução: {}", e);
-            std::process::exit(1);
+        Comando::Check => {
+            if !opcoes.quiet {
+                println!("{}: nenhum erro encontrado", fonte.nome);
+            }
+            Ok(())
+        }
+        Comando::Run => {
+            let args = converter_argumentos(&opcoes.args).map_err(|mensagem| {
+                eprintln!("erro: {}", mensagem);
+                SAIDA_USO
+            })?;
+
+            //etapa 3: interpretação
+            let mut interpreter = Interpreter::new();
+            interpreter.set_overflow_mode(opcoes.overflow);
+            match interpreter.interpret_with_args(programa, args) {
+                Ok(resultado) => {
+                    if opcoes.quiet {
+                        println!("{}", resultado);
+                    } else {
+                        if opcoes.verbose {
+                            println!("Programa executado com sucesso!");
+                        }
+                        println!("Resultado: {}", resultado);
+                    }
+                    match codigo_de_saida(&resultado) {
+                        0 => Ok(()),
+                        codigo => Err(codigo),
+                    }
+                }
+                Err(e) => {
+                    mostrar_erro(fonte, Diagnostic::from_runtime_error(&e, &interpreter));
+                    Err(SAIDA_ERRO_EXECUCAO)
+                }
+            }
         }
+        Comando::Tokens | Comando::Repl => unreachable!(),
     }
 }
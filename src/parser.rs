@@ -1,75 +1,313 @@
-use crate::lexer::Token;
-use crate::ast::{Stmt, Expr, Function, BinOp, LogicalOp, UnaryOp};
+use std::collections::HashMap;
+use crate::lexer::{Token, SpannedToken};
+use crate::span::Span;
+use crate::ast::{Program, Item, ReplInput, Stmt, StmtKind, SwitchCase, Initializer, Expr, ExprKind, Function, Param, StructDef, Field, EnumDef, Type, BinOp, LogicalOp, UnaryOp, UpdateOp};
 
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<SpannedToken>,
     current: usize,
+    errors: Vec<ParseError>,
+    // As constantes dos enums já definidos, para calcular os valores que dependem delas
+    enum_constants: HashMap<String, i64>,
+    // Quantos laços e quantos switch envolvem o statement atual, para saber se um break ou
+    // continue é permitido
+    loop_depth: usize,
+    switch_depth: usize,
+}
+
+// Um erro de sintaxe: o que era esperado, o token encontrado (None quando o arquivo acabou) e o
+// trecho do código onde o erro aconteceu
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub expected: String,
+    pub found: Option<Token>,
+    pub span: Span,
 }
 
 //implementação do funcionamento do parser, a nossa análise sintática!
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+    pub fn new(tokens: Vec<SpannedToken>) -> Self {
+        Parser { tokens, current: 0, errors: Vec::new(), enum_constants: HashMap::new(), loop_depth: 0, switch_depth: 0 }
     }
 
-    pub fn parse(&mut self) -> (Vec<Function>, Vec<Stmt>) {
-        let mut funcoes = Vec::new();
-        let mut main_body = Vec::new();
-        let mut in_main = false;
+    // Registra constantes de enum definidas antes deste código, como as de entradas anteriores do
+    // REPL, para que possam ser usadas nos valores dos case e de outros enums
+    pub fn define_constants<'a>(&mut self, constants: impl IntoIterator<Item = (&'a str, i64)>) {
+        self.enum_constants
+            .extend(constants.into_iter().map(|(name, value)| (name.to_string(), value)));
+    }
+
+    // Faz o parse do programa inteiro. Em vez de parar no primeiro erro, o parser se
+    // sincroniza e continua, devolvendo todos os erros encontrados de uma vez
+    pub fn parse(&mut self) -> Result<Program, Vec<ParseError>> {
+        let mut items = Vec::new();
 
         while !self.is_at_end() {
-            //verifica se é uma declaração de função
-            if self.check(&Token::Int) && self.peek_ahead(1).map_or(false, |t| matches!(t, Token::Ident(_))) {
-                let func = self.parse_function();
-                if func.name == "main" {
-                    in_main = true;
-                    main_body = func.body;
-                } else {
-                    funcoes.push(func);
+            match self.parse_item() {
+                Ok(item) => items.push(item),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize_item();
                 }
-            } else if in_main {
-                //Faz o parse de statements dentro da main
-                let stmt = self.parse_statement();
-                main_body.push(stmt);
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(Program { items })
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    // Faz o parse de uma entrada do REPL, que pode misturar definições de função, statements e,
+    // no final, uma expressão sem ';' cujo valor deve ser mostrado
+    pub fn parse_repl(&mut self) -> Result<Vec<ReplInput>, Vec<ParseError>> {
+        let mut inputs = Vec::new();
+
+        while !self.is_at_end() {
+            match self.parse_repl_input() {
+                Ok(input) => inputs.push(input),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
+                    //se parou num '}' solto, pula ele para não travar o laço
+                    self.match_token(&Token::FechaChave);
+                }
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(inputs)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    fn parse_repl_input(&mut self) -> Result<ReplInput, ParseError> {
+        if self.at_struct_definition() {
+            return Ok(ReplInput::Struct(self.parse_struct()?));
+        }
+        if self.at_enum_definition() {
+            return Ok(ReplInput::Enum(self.parse_enum()?));
+        }
+        if self.at_function_definition() {
+            return Ok(ReplInput::Function(self.parse_function()?));
+        }
+
+        let starts_statement = matches!(
+            self.peek(),
+            Some(
+                Token::Int
+                    | Token::Bool
+                    | Token::String
+                    | Token::Double
+                    | Token::Float
+                    | Token::Char
+                    | Token::Struct
+                    | Token::Enum
+                    | Token::Return
+                    | Token::If
+                    | Token::While
+                    | Token::Do
+                    | Token::For
+                    | Token::Switch
+                    | Token::Break
+                    | Token::Continue
+            )
+        );
+        if starts_statement {
+            return Ok(ReplInput::Stmt(self.parse_statement()?));
+        }
+
+        //uma expressão: com ';' é um statement comum, sem ';' no fim da entrada é para mostrar
+        let start = self.current_span();
+        let expr = self.parse_expression()?;
+        if self.is_at_end() {
+            return Ok(ReplInput::Expr(expr));
+        }
+        self.consume(&Token::PontoEVirgula, "';'")?;
+        Ok(ReplInput::Stmt(Stmt::new(StmtKind::ExprStmt(expr), self.span_from(start))))
+    }
+
+    fn parse_item(&mut self) -> Result<Item, ParseError> {
+        if self.at_struct_definition() {
+            return Ok(Item::Struct(self.parse_struct()?));
+        }
+        if self.at_enum_definition() {
+            return Ok(Item::Enum(self.parse_enum()?));
+        }
+
+        let type_len = self.type_len();
+        let has_name = self.peek_ahead(type_len).is_some_and(|t| matches!(t, Token::Ident(_)));
+
+        if type_len == 0 || !has_name {
+            return Err(self.error("declaração de função, struct, enum ou variável global"));
+        }
+
+        //se depois do nome vem '(' é uma função, senão é uma variável global
+        if self.at_function_definition() {
+            Ok(Item::Function(self.parse_function()?))
+        } else {
+            Ok(Item::Global(self.parse_statement()?))
+        }
+    }
+
+    //tipo, nome e '(' marcam o começo da definição de uma função
+    fn at_function_definition(&self) -> bool {
+        let type_len = self.type_len();
+        type_len > 0
+            && self.peek_ahead(type_len).is_some_and(|t| matches!(t, Token::Ident(_)))
+            && self.peek_ahead(type_len + 1) == Some(&Token::AbrePar)
+    }
+
+    //struct, nome e '{' marcam o começo da definição de uma struct
+    fn at_struct_definition(&self) -> bool {
+        self.check(&Token::Struct)
+            && self.peek_ahead(1).is_some_and(|t| matches!(t, Token::Ident(_)))
+            && self.peek_ahead(2) == Some(&Token::AbreChave)
+    }
+
+    //a definição de uma struct: struct Nome { tipo campo; ... };
+    fn parse_struct(&mut self) -> Result<StructDef, ParseError> {
+        let start = self.current_span();
+        self.consume(&Token::Struct, "'struct'")?;
+        let name = self.consume_ident("nome da struct")?;
+        self.consume(&Token::AbreChave, "'{'")?;
+
+        let mut fields: Vec<Field> = Vec::new();
+        while !self.check(&Token::FechaChave) && !self.is_at_end() {
+            let ty = self.parse_type("tipo do campo ou '}'")?;
+            let field_position = self.current;
+            let field_name = self.consume_ident("nome do campo")?;
+            if fields.iter().any(|field| field.name == field_name) {
+                let expected = format!("um nome de campo que ainda não existe em '{}'", name);
+                return Err(self.error_at(field_position, &expected));
+            }
+            self.consume(&Token::PontoEVirgula, "';'")?;
+            fields.push(Field { name: field_name, ty });
+        }
+
+        self.consume(&Token::FechaChave, "'}'")?;
+        self.consume(&Token::PontoEVirgula, "';' depois da definição da struct")?;
+        Ok(StructDef { name, fields, span: self.span_from(start) })
+    }
+
+    //enum, nome e '{' marcam o começo da definição de um enum
+    fn at_enum_definition(&self) -> bool {
+        self.check(&Token::Enum)
+            && self.peek_ahead(1).is_some_and(|t| matches!(t, Token::Ident(_)))
+            && self.peek_ahead(2) == Some(&Token::AbreChave)
+    }
+
+    //a definição de um enum: enum Nome { A, B = 5, C };. Cada constante vale o que vem depois do
+    //'=', que precisa ser um valor constante, ou a anterior mais um
+    fn parse_enum(&mut self) -> Result<EnumDef, ParseError> {
+        let start = self.current_span();
+        self.consume(&Token::Enum, "'enum'")?;
+        let name = self.consume_ident("nome do enum")?;
+        self.consume(&Token::AbreChave, "'{'")?;
+
+        let mut constants: Vec<(String, i64)> = Vec::new();
+        //None quando a constante anterior já é o maior int, e a próxima precisa de um valor escrito
+        let mut next_value = Some(0i64);
+        while !self.check(&Token::FechaChave) {
+            let constant_position = self.current;
+            let constant = self.consume_ident("nome da constante ou '}'")?;
+            if self.enum_constants.contains_key(&constant) {
+                return Err(self.error_at(constant_position, "um nome de constante que ainda não foi usado"));
+            }
+
+            let value = if self.match_token(&Token::Assign) {
+                let value_position = self.current;
+                let expr = self.parse_expression()?;
+                self.constant_value(&expr)
+                    .ok_or_else(|| self.error_at(value_position, "um valor inteiro constante"))?
             } else {
-                //avança se não conseguir fazer parse
-                self.advance();
+                next_value.ok_or_else(|| {
+                    self.error_at(constant_position, "um valor para a constante, já que a anterior é o maior int possível")
+                })?
+            };
+            next_value = value.checked_add(1);
+            self.enum_constants.insert(constant.clone(), value);
+            constants.push((constant, value));
+
+            //a vírgula depois da última constante é opcional, como em C
+            if !self.match_token(&Token::Virgula) {
+                break;
             }
         }
 
-        (funcoes, main_body)
+        self.consume(&Token::FechaChave, "'}'")?;
+        self.consume(&Token::PontoEVirgula, "';' depois da definição do enum")?;
+        Ok(EnumDef { name, constants, span: self.span_from(start) })
     }
 
-    fn parse_function(&mut self) -> Function {
-        //tipo de retorno (int ou booleano)
-        if !self.match_tokens(&[Token::Int, Token::Bool]) {
-            panic!("Esperado tipo de retorno");
+    //o valor de uma expressão que dá para calcular sem executar o programa: números, caracteres,
+    //constantes de enum já definidas e contas entre eles. None se a expressão não for constante
+    fn constant_value(&self, expr: &Expr) -> Option<i64> {
+        match &expr.kind {
+            ExprKind::Number(n) => Some(*n),
+            ExprKind::Char(c) => Some(*c as i64),
+            ExprKind::Var(name) => self.enum_constants.get(name).copied(),
+            ExprKind::Unary { op: UnaryOp::Minus, expr } => self.constant_value(expr)?.checked_neg(),
+            ExprKind::Unary { op: UnaryOp::BitNot, expr } => Some(!self.constant_value(expr)?),
+            ExprKind::Conditional { condition, then_expr, else_expr } => {
+                if self.constant_value(condition)? != 0 {
+                    self.constant_value(then_expr)
+                } else {
+                    self.constant_value(else_expr)
+                }
+            }
+            ExprKind::Binary { op, lhs, rhs } => {
+                let (l, r) = (self.constant_value(lhs)?, self.constant_value(rhs)?);
+                match op {
+                    BinOp::Add => l.checked_add(r),
+                    BinOp::Sub => l.checked_sub(r),
+                    BinOp::Mul => l.checked_mul(r),
+                    BinOp::Div => l.checked_div(r),
+                    BinOp::Mod => l.checked_rem(r),
+                    BinOp::BitAnd => Some(l & r),
+                    BinOp::BitOr => Some(l | r),
+                    BinOp::BitXor => Some(l ^ r),
+                    BinOp::ShiftLeft => u32::try_from(r).ok().and_then(|r| l.checked_shl(r)),
+                    BinOp::ShiftRight => u32::try_from(r).ok().and_then(|r| l.checked_shr(r)),
+                    _ => None,
+                }
+            }
+            _ => None,
         }
+    }
+
+    fn parse_function(&mut self) -> Result<Function, ParseError> {
+        let start = self.current_span();
+
+        //tipo de retorno
+        let return_type = self.parse_type("tipo de retorno")?;
 
         //nome da função
-        let name = if let Token::Ident(n) = self.advance() {
-            n.clone()
-        } else {
-            panic!("Esperado nome da função");
-        };
+        let name = self.consume_ident("nome da função")?;
 
         //parâmetros
-        self.consume(&Token::AbrePar, "Esperado '('");
+        self.consume(&Token::AbrePar, "'('")?;
         let mut params = Vec::new();
 
         if !self.check(&Token::FechaPar) {
             loop {
-                //tipo do parâmetro (int ou booleano)
-                if !self.match_tokens(&[Token::Int, Token::Bool]) {
-                    panic!("Esperado tipo do parâmetro");
-                }
-
-                //nome do parâmetro
-                if let Token::Ident(param_name) = self.advance() {
-                    params.push(param_name.clone());
-                } else {
-                    panic!("Esperado nome do parâmetro");
+                //tipo e nome do parâmetro, com [] depois do nome se for um array. Como em C, as
+                //dimensões depois da primeira podem trazer o tamanho (int m[][4]), que é ignorado
+                let ty = self.parse_type("tipo do parâmetro")?;
+                let name = self.consume_ident("nome do parâmetro")?;
+                let is_array = self.match_token(&Token::AbreColchete);
+                if is_array {
+                    self.consume(&Token::FechaColchete, "']'")?;
+                    while self.match_token(&Token::AbreColchete) {
+                        if !self.check(&Token::FechaColchete) {
+                            self.parse_expression()?;
+                        }
+                        self.consume(&Token::FechaColchete, "']'")?;
+                    }
                 }
+                params.push(Param { name, ty, is_array });
 
                 if !self.match_token(&Token::Virgula) {
                     break;
@@ -77,167 +315,520 @@ impl Parser {
             }
         }
 
-        self.consume(&Token::FechaPar, "Esperado ')'");
+        self.consume(&Token::FechaPar, "')'")?;
 
         //corpo da função
-        self.consume(&Token::AbreChave, "Esperado '{'");
-        let mut body = Vec::new();
+        let body = self.parse_block()?;
 
-        while !self.check(&Token::FechaChave) && !self.is_at_end() {
-            body.push(self.parse_statement());
-        }
+        Ok(Function { name, return_type, params, body, span: self.span_from(start) })
+    }
 
-        self.consume(&Token::FechaChave, "Esperado '}'");
+    fn parse_statement(&mut self) -> Result<Stmt, ParseError> {
+        let start = self.current_span();
 
-        Function { name, params, body }
-    }
+        //declaração de variável: tipo e nome, com '= valor' ou '= {lista}' opcional
+        if self.check_type() {
+            let ty = self.parse_type("tipo")?;
+            let name = self.consume_ident("nome da variável")?;
+
+            if self.match_token(&Token::AbreColchete) {
+                return self.parse_array_declaration(name, ty, start);
+            }
 
-    fn parse_statement(&mut self) -> Stmt {
-        //declaração de variável: int/booleano e nome = expressão;
-        if self.match_tokens(&[Token::Int, Token::Bool]) {
-            let name = if let Token::Ident(n) = self.advance() {
-                n.clone()
+            let init = if !self.match_token(&Token::Assign) {
+                None
+            } else if self.check(&Token::AbreChave) {
+                Some(self.parse_initializer_list()?)
             } else {
-                panic!("Esperado nome da variável");
+                Some(Initializer::Value(self.parse_expression()?))
             };
+            self.consume(&Token::PontoEVirgula, "';'")?;
 
-            self.consume(&Token::Assign, "Esperado '='");
-            let value = self.parse_expression();
-            self.consume(&Token::PontoEVirgula, "Esperado ';'");
-
-            return Stmt::VarDecl { name, value };
+            return Ok(Stmt::new(StmtKind::VarDecl { name, ty, init }, self.span_from(start)));
         }
 
         //o uso do retorno
         if self.match_token(&Token::Return) {
-            let expr = self.parse_expression();
-            self.consume(&Token::PontoEVirgula, "Esperado ';'");
-            return Stmt::Return(expr);
+            let expr = self.parse_expression()?;
+            self.consume(&Token::PontoEVirgula, "';'")?;
+            return Ok(Stmt::new(StmtKind::Return(expr), self.span_from(start)));
         }
 
         //o uso do if
         if self.match_token(&Token::If) {
-            self.consume(&Token::AbrePar, "Esperado '('");
-            let condition = self.parse_expression();
-            self.consume(&Token::FechaPar, "Esperado ')'");
+            self.consume(&Token::AbrePar, "'('")?;
+            let condition = self.parse_expression()?;
+            self.consume(&Token::FechaPar, "')'")?;
 
-            let then_branch = self.parse_block();
+            let then_branch = self.parse_block()?;
 
             let else_branch = if self.match_token(&Token::Else) {
-                Some(self.parse_block())
+                Some(self.parse_block()?)
             } else {
                 None
             };
 
-            return Stmt::If { condition, then_branch, else_branch };
+            return Ok(Stmt::new(StmtKind::If { condition, then_branch, else_branch }, self.span_from(start)));
         }
 
         //o uso do while
         if self.match_token(&Token::While) {
-            self.consume(&Token::AbrePar, "Esperado '('");
-            let condition = self.parse_expression();
-            self.consume(&Token::FechaPar, "Esperado ')'");
+            self.consume(&Token::AbrePar, "'('")?;
+            let condition = self.parse_expression()?;
+            self.consume(&Token::FechaPar, "')'")?;
 
-            let body = self.parse_block();
+            let body = self.parse_loop_body()?;
 
-            return Stmt::While { condition, body };
+            return Ok(Stmt::new(StmtKind::While { condition, body }, self.span_from(start)));
+        }
+
+        //o uso do do-while, que termina com ';'
+        if self.match_token(&Token::Do) {
+            let body = self.parse_loop_body()?;
+
+            self.consume(&Token::While, "'while' depois do corpo do 'do'")?;
+            self.consume(&Token::AbrePar, "'('")?;
+            let condition = self.parse_expression()?;
+            self.consume(&Token::FechaPar, "')'")?;
+            self.consume(&Token::PontoEVirgula, "';'")?;
+
+            return Ok(Stmt::new(StmtKind::DoWhile { body, condition }, self.span_from(start)));
         }
 
         //o uso do for
         if self.match_token(&Token::For) {
-            self.consume(&Token::AbrePar, "Esperado '('");
+            self.consume(&Token::AbrePar, "'('")?;
 
             //inicialização (opcional)
             let init = if self.check(&Token::PontoEVirgula) {
                 None
             } else {
-                Some(Box::new(self.parse_statement()))
+                Some(Box::new(self.parse_statement()?))
             };
 
             if init.is_none() {
-                self.consume(&Token::PontoEVirgula, "Esperado ';'");
+                self.consume(&Token::PontoEVirgula, "';'")?;
             }
 
             //condição (opcional)
             let condition = if self.check(&Token::PontoEVirgula) {
                 None
             } else {
-                Some(self.parse_expression())
+                Some(self.parse_expression()?)
             };
-            self.consume(&Token::PontoEVirgula, "Esperado ';'");
+            self.consume(&Token::PontoEVirgula, "';'")?;
 
             //atualização (opcional)
             let update = if self.check(&Token::FechaPar) {
                 None
             } else {
-                Some(self.parse_expression())
+                Some(self.parse_expression()?)
             };
-            self.consume(&Token::FechaPar, "Esperado ')'");
+            self.consume(&Token::FechaPar, "')'")?;
 
-            let body = self.parse_block();
+            let body = self.parse_loop_body()?;
 
-            return Stmt::For { init, condition, update, body };
+            return Ok(Stmt::new(StmtKind::For { init, condition, update, body }, self.span_from(start)));
+        }
+
+        //o uso do switch
+        if self.match_token(&Token::Switch) {
+            self.consume(&Token::AbrePar, "'('")?;
+            let subject = self.parse_expression()?;
+            self.consume(&Token::FechaPar, "')'")?;
+            self.consume(&Token::AbreChave, "'{'")?;
+
+            self.switch_depth += 1;
+            let cases = self.parse_switch_cases();
+            self.switch_depth -= 1;
+            let cases = cases?;
+
+            self.consume(&Token::FechaChave, "'}'")?;
+            return Ok(Stmt::new(StmtKind::Switch { subject, cases }, self.span_from(start)));
+        }
+
+        //o break, que só faz sentido dentro de um laço ou switch
+        if self.match_token(&Token::Break) {
+            if self.loop_depth == 0 && self.switch_depth == 0 {
+                return Err(self.error_at(
+                    self.current - 1,
+                    "statement ('break' só pode ser usado dentro de um laço ou switch)",
+                ));
+            }
+            self.consume(&Token::PontoEVirgula, "';'")?;
+            return Ok(Stmt::new(StmtKind::Break, self.span_from(start)));
+        }
+
+        //o continue, que só faz sentido dentro de um laço
+        if self.match_token(&Token::Continue) {
+            if self.loop_depth == 0 {
+                return Err(self.error_at(self.current - 1, "statement ('continue' só pode ser usado dentro de um laço)"));
+            }
+            self.consume(&Token::PontoEVirgula, "';'")?;
+            return Ok(Stmt::new(StmtKind::Continue, self.span_from(start)));
         }
 
         //o uso da expressão
-        let expr = self.parse_expression();
-        self.consume(&Token::PontoEVirgula, "Esperado ';'");
-        Stmt::ExprStmt(expr)
+        let expr = self.parse_expression()?;
+        self.consume(&Token::PontoEVirgula, "';'")?;
+        Ok(Stmt::new(StmtKind::ExprStmt(expr), self.span_from(start)))
+    }
+
+    //o corpo de um laço, onde break e continue são permitidos
+    fn parse_loop_body(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        self.loop_depth += 1;
+        let body = self.parse_block();
+        self.loop_depth -= 1;
+        body
+    }
+
+    //os cases de um switch, até o '}'. Cada valor precisa ser constante (um número, um caractere ou
+    //uma constante de enum) e não pode se repetir, e só pode haver um default
+    fn parse_switch_cases(&mut self) -> Result<Vec<SwitchCase>, ParseError> {
+        let mut cases: Vec<SwitchCase> = Vec::new();
+
+        while !self.check(&Token::FechaChave) && !self.is_at_end() {
+            let start = self.current_span();
+            //um case com erro é registrado e pulado até o ':', e os statements dele ainda são
+            //analisados para mostrar os erros que tiverem
+            let label = match self.parse_case_label(&cases) {
+                Ok(label) => Some(label),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.skip_case_label();
+                    None
+                }
+            };
+
+            //os statements do case vão até o próximo case, default ou o fim do switch
+            let mut body = Vec::new();
+            while !self.check(&Token::Case)
+                && !self.check(&Token::Default)
+                && !self.check(&Token::FechaChave)
+                && !self.is_at_end()
+            {
+                match self.parse_statement() {
+                    Ok(stmt) => body.push(stmt),
+                    Err(error) => {
+                        self.errors.push(error);
+                        self.synchronize();
+                    }
+                }
+            }
+
+            if let Some(label) = label {
+                cases.push(SwitchCase { label, body, span: self.span_from(start) });
+            }
+        }
+
+        Ok(cases)
+    }
+
+    //o começo de um case, até o ':': o valor do case, ou None no default
+    fn parse_case_label(&mut self, cases: &[SwitchCase]) -> Result<Option<i64>, ParseError> {
+        let label = if self.match_token(&Token::Case) {
+            let label_position = self.current;
+            let expr = self.parse_expression()?;
+            let value = self
+                .constant_value(&expr)
+                .ok_or_else(|| self.error_at(label_position, "um valor inteiro constante no 'case'"))?;
+            if cases.iter().any(|case| case.label == Some(value)) {
+                return Err(self.error_at(label_position, "um valor que ainda não apareceu em outro 'case'"));
+            }
+            Some(value)
+        } else if self.match_token(&Token::Default) {
+            if cases.iter().any(|case| case.label.is_none()) {
+                return Err(self.error_at(self.current - 1, "'case' ('default' só pode aparecer uma vez)"));
+            }
+            None
+        } else {
+            return Err(self.error("'case' ou 'default'"));
+        };
+        self.consume(&Token::DoisPontos, "':'")?;
+        Ok(label)
+    }
+
+    //recuperação de erros no começo de um case: descarta tokens até depois do ':' ou até o
+    //próximo case, default ou o fim do switch
+    fn skip_case_label(&mut self) {
+        while !self.check(&Token::Case)
+            && !self.check(&Token::Default)
+            && !self.check(&Token::FechaChave)
+            && !self.is_at_end()
+        {
+            if self.match_token(&Token::DoisPontos) {
+                return;
+            }
+            self.advance();
+        }
+    }
+
+    //o resto da declaração de um array, depois do primeiro '[': o tamanho de cada dimensão (só o
+    //da primeira pode ser omitido, quando há inicializador) e o inicializador opcional entre chaves
+    fn parse_array_declaration(&mut self, name: String, ty: Type, start: Span) -> Result<Stmt, ParseError> {
+        let mut dims = Vec::new();
+        loop {
+            let size = if self.check(&Token::FechaColchete) {
+                if !dims.is_empty() {
+                    return Err(self.error("tamanho da dimensão do array"));
+                }
+                None
+            } else {
+                Some(self.parse_expression()?)
+            };
+            self.consume(&Token::FechaColchete, "']'")?;
+            dims.push(size);
+
+            if !self.match_token(&Token::AbreColchete) {
+                break;
+            }
+        }
+
+        let init = if self.match_token(&Token::Assign) {
+            Some(self.parse_initializer_list()?)
+        } else if dims[0].is_none() {
+            return Err(self.error("'=' e os valores do array, já que o tamanho foi omitido"));
+        } else {
+            None
+        };
+        self.consume(&Token::PontoEVirgula, "';'")?;
+
+        Ok(Stmt::new(StmtKind::ArrayDecl { name, ty, dims, init }, self.span_from(start)))
+    }
+
+    //a lista entre chaves que inicializa um array ou uma struct, como {1, 2, 3}. Cada item pode ser
+    //outra lista, para os arrays de mais de uma dimensão e as structs dentro de structs
+    fn parse_initializer_list(&mut self) -> Result<Initializer, ParseError> {
+        let start = self.current_span();
+        self.consume(&Token::AbreChave, "'{'")?;
+        let mut items = Vec::new();
+        if !self.check(&Token::FechaChave) {
+            loop {
+                if self.check(&Token::AbreChave) {
+                    items.push(self.parse_initializer_list()?);
+                } else {
+                    items.push(Initializer::Value(self.parse_expression()?));
+                }
+                if !self.match_token(&Token::Virgula) {
+                    break;
+                }
+            }
+        }
+        self.consume(&Token::FechaChave, "'}'")?;
+        Ok(Initializer::List(items, self.span_from(start)))
     }
 
-    //o parse do block
-    fn parse_block(&mut self) -> Vec<Stmt> {
-        self.consume(&Token::AbreChave, "Esperado '{'");
+    //o parse do block. Um erro num statement é registrado e o parser se sincroniza no próximo
+    //';' ou '}', para continuar analisando o resto do bloco
+    fn parse_block(&mut self) -> Result<Vec<Stmt>, ParseError> {
+        self.consume(&Token::AbreChave, "'{'")?;
         let mut statements = Vec::new();
 
         while !self.check(&Token::FechaChave) && !self.is_at_end() {
-            statements.push(self.parse_statement());
+            match self.parse_statement() {
+                Ok(stmt) => statements.push(stmt),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize();
+                }
+            }
         }
 
-        self.consume(&Token::FechaChave, "Esperado '}'");
-        statements
+        self.consume(&Token::FechaChave, "'}'")?;
+        Ok(statements)
     }
 
     //o parse da expressão
-    fn parse_expression(&mut self) -> Expr {
-        self.parse_logical_or()
+    fn parse_expression(&mut self) -> Result<Expr, ParseError> {
+        self.parse_assignment()
+    }
+
+    //o parse da atribuição, que tem a menor precedência e é associativa à direita (a = b = 1)
+    fn parse_assignment(&mut self) -> Result<Expr, ParseError> {
+        let expr = self.parse_conditional()?;
+
+        if self.match_token(&Token::Assign) {
+            let operator_position = self.current - 1;
+            if !expr.is_assignable() {
+                return Err(self.error_at(operator_position, "variável ou elemento de array antes de '='"));
+            }
+            let value = self.parse_assignment()?;
+            let span = expr.span.to(value.span);
+            return Ok(Expr::new(
+                ExprKind::Assign {
+                    target: Box::new(expr),
+                    value: Box::new(value),
+                },
+                span,
+            ));
+        }
+
+        //atribuições compostas (+=, -=, *=, /=, %=)
+        if self.match_tokens(&[
+            Token::MaisIgual,
+            Token::MenosIgual,
+            Token::MultiplicaIgual,
+            Token::DivideIgual,
+            Token::RestoIgual,
+        ]) {
+            let operator_position = self.current - 1;
+            let op = match self.previous() {
+                Token::MaisIgual => BinOp::Add,
+                Token::MenosIgual => BinOp::Sub,
+                Token::MultiplicaIgual => BinOp::Mul,
+                Token::DivideIgual => BinOp::Div,
+                Token::RestoIgual => BinOp::Mod,
+                _ => unreachable!(),
+            };
+            if !expr.is_assignable() {
+                return Err(self.error_at(
+                    operator_position,
+                    "variável ou elemento de array antes da atribuição composta",
+                ));
+            }
+            let value = self.parse_assignment()?;
+            let span = expr.span.to(value.span);
+            return Ok(Expr::new(
+                ExprKind::CompoundAssign {
+                    target: Box::new(expr),
+                    op,
+                    value: Box::new(value),
+                },
+                span,
+            ));
+        }
+
+        Ok(expr)
+    }
+
+    //o operador condicional (a ? b : c), logo abaixo do ||. Como em C, o valor do meio pode ser
+    //qualquer expressão, e o último pode ter outro condicional, que fica associado à direita
+    //(a ? b : c ? d : e é a ? b : (c ? d : e))
+    fn parse_conditional(&mut self) -> Result<Expr, ParseError> {
+        let condition = self.parse_logical_or()?;
+
+        if !self.match_token(&Token::Interrogacao) {
+            return Ok(condition);
+        }
+        let then_expr = self.parse_expression()?;
+        self.consume(&Token::DoisPontos, "':' do operador condicional")?;
+        let else_expr = self.parse_conditional()?;
+
+        let span = condition.span.to(else_expr.span);
+        Ok(Expr::new(
+            ExprKind::Conditional {
+                condition: Box::new(condition),
+                then_expr: Box::new(then_expr),
+                else_expr: Box::new(else_expr),
+            },
+            span,
+        ))
     }
 
     //o parse da expressão lógica or
-    fn parse_logical_or(&mut self) -> Expr {
-        let mut expr = self.parse_logical_and();
+    fn parse_logical_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_logical_and()?;
 
         while self.match_token(&Token::Or) {
-            let right = self.parse_logical_and();
-            expr = Expr::Logical {
-                op: LogicalOp::Or,
-                lhs: Box::new(expr),
-                rhs: Box::new(right),
-            };
+            let right = self.parse_logical_and()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Logical {
+                    op: LogicalOp::Or,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
         }
 
-        expr
+        Ok(expr)
     }
 
     //o parse da exŕessão lógica and
-    fn parse_logical_and(&mut self) -> Expr {
-        let mut expr = self.parse_equality();
+    fn parse_logical_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_bit_or()?;
 
         while self.match_token(&Token::And) {
-            let right = self.parse_equality();
-            expr = Expr::Logical {
-                op: LogicalOp::And,
-                lhs: Box::new(expr),
-                rhs: Box::new(right),
-            };
+            let right = self.parse_bit_or()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Logical {
+                    op: LogicalOp::And,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
+        }
+
+        Ok(expr)
+    }
+
+    //os operadores bit a bit ficam entre o && e a igualdade, na ordem de C: primeiro o |,
+    //depois o ^ e por último o &, que é o que tem mais precedência
+    fn parse_bit_or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_bit_xor()?;
+
+        while self.match_token(&Token::BitOr) {
+            let right = self.parse_bit_xor()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Binary {
+                    op: BinOp::BitOr,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
+        }
+
+        Ok(expr)
+    }
+
+    //o parse do ou exclusivo bit a bit
+    fn parse_bit_xor(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_bit_and()?;
+
+        while self.match_token(&Token::BitXor) {
+            let right = self.parse_bit_and()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Binary {
+                    op: BinOp::BitXor,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
         }
 
-        expr
+        Ok(expr)
+    }
+
+    //o parse do e bit a bit
+    fn parse_bit_and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_equality()?;
+
+        while self.match_token(&Token::BitAnd) {
+            let right = self.parse_equality()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Binary {
+                    op: BinOp::BitAnd,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
+        }
+
+        Ok(expr)
     }
 
     //o parse da igualdade, caso uma expressãp seja igual à outra e afins
-    fn parse_equality(&mut self) -> Expr {
-        let mut expr = self.parse_comparison();
+    fn parse_equality(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_comparison()?;
 
         while self.match_tokens(&[Token::Equal, Token::NotEqual]) {
             let op = match self.previous() {
@@ -245,20 +836,24 @@ impl Parser {
                 Token::NotEqual => BinOp::NotEqual,
                 _ => unreachable!(),
             };
-            let right = self.parse_comparison();
-            expr = Expr::Binary {
-                op,
-                lhs: Box::new(expr),
-                rhs: Box::new(right),
-            };
+            let right = self.parse_comparison()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Binary {
+                    op,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
         }
 
-        expr
+        Ok(expr)
     }
 
     //aqui é o parse da comparação
-    fn parse_comparison(&mut self) -> Expr {
-        let mut expr = self.parse_additive();
+    fn parse_comparison(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_shift()?;
 
         while self.match_tokens(&[Token::Greater, Token::GreaterEqual, Token::Less, Token::LessEqual]) {
             let op = match self.previous() {
@@ -268,20 +863,49 @@ impl Parser {
                 Token::LessEqual => BinOp::LessEqual,
                 _ => unreachable!(),
             };
-            let right = self.parse_additive();
-            expr = Expr::Binary {
-                op,
-                lhs: Box::new(expr),
-                rhs: Box::new(right),
+            let right = self.parse_shift()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Binary {
+                    op,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
+        }
+
+        Ok(expr)
+    }
+
+    //os deslocamentos (<< e >>), entre a comparação e a adição
+    fn parse_shift(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_additive()?;
+
+        while self.match_tokens(&[Token::ShiftLeft, Token::ShiftRight]) {
+            let op = match self.previous() {
+                Token::ShiftLeft => BinOp::ShiftLeft,
+                Token::ShiftRight => BinOp::ShiftRight,
+                _ => unreachable!(),
             };
+            let right = self.parse_additive()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Binary {
+                    op,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
         }
 
-        expr
+        Ok(expr)
     }
 
     //aqui é o parse que regulamente adição e subtração
-    fn parse_additive(&mut self) -> Expr {
-        let mut expr = self.parse_multiplicative();
+    fn parse_additive(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_multiplicative()?;
 
         while self.match_tokens(&[Token::Mais, Token::Menos]) {
             let op = match self.previous() {
@@ -289,77 +913,184 @@ impl Parser {
                 Token::Menos => BinOp::Sub,
                 _ => unreachable!(),
             };
-            let right = self.parse_multiplicative();
-            expr = Expr::Binary {
-                op,
-                lhs: Box::new(expr),
-                rhs: Box::new(right),
-            };
+            let right = self.parse_multiplicative()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Binary {
+                    op,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
         }
 
-        expr
+        Ok(expr)
     }
 
-    //aqui é o parse da multiplicação/divisão
-    fn parse_multiplicative(&mut self) -> Expr {
-        let mut expr = self.parse_unary();
+    //aqui é o parse da multiplicação/divisão/resto
+    fn parse_multiplicative(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_unary()?;
 
-        while self.match_tokens(&[Token::Multiplica, Token::Divide]) {
+        while self.match_tokens(&[Token::Multiplica, Token::Divide, Token::Resto]) {
             let op = match self.previous() {
                 Token::Multiplica => BinOp::Mul,
                 Token::Divide => BinOp::Div,
+                Token::Resto => BinOp::Mod,
                 _ => unreachable!(),
             };
-            let right = self.parse_unary();
-            expr = Expr::Binary {
-                op,
-                lhs: Box::new(expr),
-                rhs: Box::new(right),
-            };
+            let right = self.parse_unary()?;
+            let span = expr.span.to(right.span);
+            expr = Expr::new(
+                ExprKind::Binary {
+                    op,
+                    lhs: Box::new(expr),
+                    rhs: Box::new(right),
+                },
+                span,
+            );
         }
 
-        expr
+        Ok(expr)
     }
 
     //esse parse mexe com os valores unários, como não, por exemplo, tal como tinha na gramática e
     //parser do Lox
-    fn parse_unary(&mut self) -> Expr {
-        if self.match_tokens(&[Token::Not, Token::Menos]) {
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        //incremento/decremento prefixado (++x, --x)
+        if self.match_tokens(&[Token::Incremento, Token::Decremento]) {
+            let operator_position = self.current - 1;
+            let op = match self.previous() {
+                Token::Incremento => UpdateOp::Increment,
+                Token::Decremento => UpdateOp::Decrement,
+                _ => unreachable!(),
+            };
+            let operand = self.parse_unary()?;
+            if !operand.is_assignable() {
+                return Err(self.error_at(operator_position, "variável ou elemento de array depois de '++'/'--'"));
+            }
+            let span = self.span_at(operator_position).to(operand.span);
+            return Ok(Expr::new(ExprKind::PrefixUpdate { op, target: Box::new(operand) }, span));
+        }
+
+        if self.match_tokens(&[Token::Not, Token::Menos, Token::BitNot]) {
+            let start = self.previous_span();
             let op = match self.previous() {
                 Token::Not => UnaryOp::Not,
                 Token::Menos => UnaryOp::Minus,
+                Token::BitNot => UnaryOp::BitNot,
                 _ => unreachable!(),
             };
-            let expr = self.parse_unary();
-            return Expr::Unary {
-                op,
-                expr: Box::new(expr),
-            };
+            let expr = self.parse_unary()?;
+            let span = start.to(expr.span);
+            return Ok(Expr::new(
+                ExprKind::Unary {
+                    op,
+                    expr: Box::new(expr),
+                },
+                span,
+            ));
+        }
+
+        //conversão explícita: um tipo entre parênteses, como em (int)x
+        let is_cast = self.check(&Token::AbrePar)
+            && Self::token_type(self.peek_ahead(1)).is_some()
+            && self.peek_ahead(2) == Some(&Token::FechaPar);
+        if is_cast {
+            let start = self.current_span();
+            self.advance();
+            let ty = self.parse_type("tipo")?;
+            self.advance();
+            let expr = self.parse_unary()?;
+            let span = start.to(expr.span);
+            return Ok(Expr::new(ExprKind::Cast { ty, expr: Box::new(expr) }, span));
         }
 
-        self.parse_primary()
+        self.parse_postfix()
+    }
+
+    //aqui é o parse dos operadores pós-fixados: indexação (v[i]), campo (p.x) e x++ e x--
+    fn parse_postfix(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_primary()?;
+
+        loop {
+            if self.match_token(&Token::AbreColchete) {
+                let index = self.parse_expression()?;
+                self.consume(&Token::FechaColchete, "']'")?;
+                let span = expr.span.to(self.previous_span());
+                expr = Expr::new(
+                    ExprKind::Index {
+                        array: Box::new(expr),
+                        index: Box::new(index),
+                    },
+                    span,
+                );
+            } else if self.match_token(&Token::Ponto) {
+                let field = self.consume_ident("nome do campo")?;
+                let span = expr.span.to(self.previous_span());
+                expr = Expr::new(ExprKind::Field { object: Box::new(expr), field }, span);
+            } else if self.match_tokens(&[Token::Incremento, Token::Decremento]) {
+                let operator_position = self.current - 1;
+                let op = match self.previous() {
+                    Token::Incremento => UpdateOp::Increment,
+                    Token::Decremento => UpdateOp::Decrement,
+                    _ => unreachable!(),
+                };
+                if !expr.is_assignable() {
+                    return Err(self.error_at(operator_position, "variável ou elemento de array antes de '++'/'--'"));
+                }
+                let span = expr.span.to(self.previous_span());
+                expr = Expr::new(ExprKind::PostfixUpdate { op, target: Box::new(expr) }, span);
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
     }
 
     //aqui é o parse de tipos primários, como o número, booleano etc.
-    fn parse_primary(&mut self) -> Expr {
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let start = self.current_span();
+
         //número
-        if let Token::Number(n) = self.peek() {
+        if let Some(Token::Number(n)) = self.peek() {
             let num = *n;
             self.advance();
-            return Expr::Number(num);
+            return Ok(Expr::new(ExprKind::Number(num), start));
+        }
+
+        if let Some(Token::Decimal(n)) = self.peek() {
+            let num = *n;
+            self.advance();
+            return Ok(Expr::new(ExprKind::Float(num), start));
+        }
+
+        //texto
+        if let Some(Token::Str(text)) = self.peek() {
+            let text = text.clone();
+            self.advance();
+            return Ok(Expr::new(ExprKind::Str(text), start));
+        }
+
+        //caractere
+        if let Some(Token::Character(c)) = self.peek() {
+            let c = *c;
+            self.advance();
+            return Ok(Expr::new(ExprKind::Char(c), start));
         }
 
         //booleanos
         if self.match_token(&Token::True) {
-            return Expr::Bool(true);
+            return Ok(Expr::new(ExprKind::Bool(true), start));
         }
 
         if self.match_token(&Token::False) {
-            return Expr::Bool(false);
+            return Ok(Expr::new(ExprKind::Bool(false), start));
         }
 
         //identificador (variável ou chamada de função)
-        if let Token::Ident(name) = self.peek() {
+        if let Some(Token::Ident(name)) = self.peek() {
             let name = name.clone();
             self.advance();
 
@@ -369,29 +1100,112 @@ impl Parser {
 
                 if !self.check(&Token::FechaPar) {
                     loop {
-                        args.push(self.parse_expression());
+                        args.push(self.parse_expression()?);
                         if !self.match_token(&Token::Virgula) {
                             break;
                         }
                     }
                 }
 
-                self.consume(&Token::FechaPar, "Esperado ')'");
-                return Expr::Call { name, args };
+                self.consume(&Token::FechaPar, "')'")?;
+                return Ok(Expr::new(ExprKind::Call { name, args }, self.span_from(start)));
             }
 
             //é uma variável
-            return Expr::Var(name);
+            return Ok(Expr::new(ExprKind::Var(name), start));
         }
 
         //expressão entre parênteses
         if self.match_token(&Token::AbrePar) {
-            let expr = self.parse_expression();
-            self.consume(&Token::FechaPar, "Esperado ')'");
-            return expr;
+            let mut expr = self.parse_expression()?;
+            self.consume(&Token::FechaPar, "')'")?;
+            expr.span = self.span_from(start);
+            return Ok(expr);
         }
 
-        panic!("Expressão inválida");
+        Err(self.error("expressão"))
+    }
+
+    //recuperação de erros: descarta tokens até o fim do statement atual (depois de um ';'),
+    //até um '}' que fecha o bloco ou até uma palavra-chave que começa um novo statement. Um bloco
+    //entre chaves no meio do caminho (o corpo de um if ou while com erro no cabeçalho) é pulado
+    //inteiro, para que os statements e o '}' dele não sejam confundidos com os do bloco de fora
+    fn synchronize(&mut self) {
+        while !self.is_at_end() {
+            if self.match_token(&Token::PontoEVirgula) {
+                return;
+            }
+
+            match self.peek() {
+                Some(Token::AbreChave) => {
+                    self.skip_braces();
+                    //depois do bloco do if ainda pode vir o else, que faz parte do mesmo statement
+                    if !self.check(&Token::Else) {
+                        return;
+                    }
+                }
+                Some(Token::FechaChave)
+                | Some(Token::Int)
+                | Some(Token::Bool)
+                | Some(Token::String)
+                | Some(Token::Double)
+                | Some(Token::Float)
+                | Some(Token::Char)
+                | Some(Token::Struct)
+                | Some(Token::Enum)
+                | Some(Token::Return)
+                | Some(Token::If)
+                | Some(Token::While)
+                | Some(Token::Do)
+                | Some(Token::For)
+                | Some(Token::Switch)
+                | Some(Token::Break)
+                | Some(Token::Continue) => return,
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    //pula um bloco entre chaves inteiro, incluindo os blocos dentro dele
+    fn skip_braces(&mut self) {
+        let mut depth = 0usize;
+
+        while !self.is_at_end() {
+            match self.advance() {
+                Token::AbreChave => depth += 1,
+                Token::FechaChave => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    //recuperação de erros no escopo do arquivo: pula o item inteiro, respeitando as chaves,
+    //até um ';' ou '}' que não esteja dentro de nenhum bloco. O ';' logo depois do '}' que fecha
+    //uma struct ou enum também é pulado
+    fn synchronize_item(&mut self) {
+        let mut depth = 0usize;
+
+        while !self.is_at_end() {
+            match self.advance() {
+                Token::AbreChave => depth += 1,
+                Token::FechaChave => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        self.match_token(&Token::PontoEVirgula);
+                        return;
+                    }
+                }
+                Token::PontoEVirgula if depth == 0 => return,
+                _ => {}
+            }
+        }
     }
 
     //métodos utilitários
@@ -404,6 +1218,51 @@ impl Parser {
         }
     }
 
+    //o tipo que o token representa, se ele for um nome de tipo
+    fn token_type(token: Option<&Token>) -> Option<Type> {
+        match token? {
+            Token::Int => Some(Type::Int),
+            Token::Bool => Some(Type::Bool),
+            Token::String => Some(Type::String),
+            Token::Double | Token::Float => Some(Type::Double),
+            Token::Char => Some(Type::Char),
+            _ => None,
+        }
+    }
+
+    //quantos tokens o tipo no token atual ocupa: 1 para int, bool etc., 2 para struct Nome e enum
+    //Nome e 0 se o token atual não começa um tipo
+    fn type_len(&self) -> usize {
+        if Self::token_type(self.peek()).is_some() {
+            1
+        } else if self.check(&Token::Struct) || self.check(&Token::Enum) {
+            2
+        } else {
+            0
+        }
+    }
+
+    //verifica se o token atual começa um tipo
+    fn check_type(&self) -> bool {
+        self.type_len() > 0
+    }
+
+    //consome o tipo, que pode ser um nome de tipo, struct Nome ou enum Nome (que é um int)
+    fn parse_type(&mut self, expected: &str) -> Result<Type, ParseError> {
+        if let Some(ty) = Self::token_type(self.peek()) {
+            self.advance();
+            return Ok(ty);
+        }
+        if self.match_token(&Token::Struct) {
+            return Ok(Type::Struct(self.consume_ident("nome da struct")?));
+        }
+        if self.match_token(&Token::Enum) {
+            self.consume_ident("nome do enum")?;
+            return Ok(Type::Int);
+        }
+        Err(self.error(expected))
+    }
+
     //verifica se os tokens correspondem a tokens esperados
     fn match_tokens(&mut self, tokens: &[Token]) -> bool {
         for token in tokens {
@@ -417,10 +1276,9 @@ impl Parser {
 
     //verifica se o tipo de token é o mesmo do fornecido
     fn check(&self, token: &Token) -> bool {
-        if self.is_at_end() {
-            false
-        } else {
-            std::mem::discriminant(self.peek()) == std::mem::discriminant(token)
+        match self.peek() {
+            Some(current) => std::mem::discriminant(current) == std::mem::discriminant(token),
+            None => false,
         }
     }
 
@@ -437,32 +1295,87 @@ impl Parser {
         self.current >= self.tokens.len()
     }
 
-    //retorna o token atual, mas sem avançar
-    fn peek(&self) -> &Token {
-        &self.tokens[self.current]
+    //retorna o token atual, mas sem avançar (None se a lista acabou)
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.current).map(|t| &t.token)
     }
 
     //verifica tokens mais à frente na lista de tokens
     fn peek_ahead(&self, distance: usize) -> Option<&Token> {
-        let index = self.current + distance;
-        if index < self.tokens.len() {
-            Some(&self.tokens[index])
-        } else {
-            None
-        }
+        self.tokens.get(self.current + distance).map(|t| &t.token)
     }
 
     //retorna o token anterior em relação ao atual
     fn previous(&self) -> &Token {
-        &self.tokens[self.current - 1]
+        &self.tokens[self.current - 1].token
+    }
+
+    //o span do token numa posição; depois do último token, é um span vazio logo após ele
+    fn span_at(&self, position: usize) -> Span {
+        match self.tokens.get(position) {
+            Some(t) => t.span,
+            None => self.tokens.last().map_or(Span::default(), |t| t.span.after()),
+        }
+    }
+
+    //o span do token atual
+    fn current_span(&self) -> Span {
+        self.span_at(self.current)
+    }
+
+    //o span do token anterior
+    fn previous_span(&self) -> Span {
+        self.span_at(self.current.saturating_sub(1))
+    }
+
+    //o span que vai de `start` até o último token consumido
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous_span())
     }
 
     //verifica se o token atual é o esperado e o consome
-    fn consume(&mut self, token: &Token, message: &str) {
+    fn consume(&mut self, token: &Token, expected: &str) -> Result<(), ParseError> {
         if self.check(token) {
             self.advance();
+            Ok(())
         } else {
-            panic!("{}", message);
+            Err(self.error(expected))
+        }
+    }
+
+    //consome um identificador e devolve o nome dele
+    fn consume_ident(&mut self, expected: &str) -> Result<String, ParseError> {
+        if let Some(Token::Ident(name)) = self.peek() {
+            let name = name.clone();
+            self.advance();
+            Ok(name)
+        } else {
+            Err(self.error(expected))
+        }
+    }
+
+    //monta um erro apontando para o token atual
+    fn error(&self, expected: &str) -> ParseError {
+        self.error_at(self.current, expected)
+    }
+
+    //monta um erro apontando para o token numa posição específica
+    fn error_at(&self, position: usize, expected: &str) -> ParseError {
+        ParseError {
+            expected: expected.to_string(),
+            found: self.tokens.get(position).map(|t| t.token.clone()),
+            span: self.span_at(position),
         }
     }
 }
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.found {
+            Some(token) => write!(f, "Erro de sintaxe: esperado {}, mas encontrado {}", self.expected, token),
+            None => write!(f, "Erro de sintaxe: esperado {}, mas o arquivo acabou", self.expected),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,315 @@
+// O REPL (read-eval-print loop) lê o programa aos poucos, linha por linha. O interpretador é o
+// mesmo entre uma entrada e outra, então funções definidas e variáveis declaradas continuam
+// valendo até um :reset
+use std::io::{self, Write};
+use crate::ast::ReplInput;
+use crate::builtins::SharedOutput;
+use crate::diagnostics::Diagnostic;
+use crate::interpreter::{Interpreter, OverflowMode, RuntimeError, Value};
+use crate::lexer::{LexErrorKind, Lexer, SpannedToken, Token};
+use crate::parser::Parser;
+
+const AJUDA: &str = "\
+Comandos:
+  :ast <código>     mostra a AST do código, sem executá-lo
+  :tokens <código>  mostra os tokens do código
+  :funcs            lista as funções definidas
+  :load <arquivo>   carrega as funções e variáveis globais de um arquivo .mc
+  :reset            esquece todas as funções e variáveis
+  :help             mostra esta ajuda
+  :quit             sai do REPL
+Expressões sem ';' no final têm o valor mostrado.
+";
+
+// O nome usado nas mensagens de erro do que foi digitado
+const NOME_ENTRADA: &str = "<repl>";
+
+pub struct Repl {
+    interpreter: Interpreter,
+    // linhas de uma entrada que ainda não terminou (chaves ou parênteses abertos)
+    pending: String,
+    // o que o programa imprime com print, println e printf, repassado para a saída de feed_line
+    output: SharedOutput,
+    overflow_mode: OverflowMode,
+    // o código de cada entrada já executada e de cada arquivo carregado, para mostrar os erros
+    // de execução no trecho certo mesmo quando ele veio de uma entrada anterior
+    sources: Vec<Source>,
+}
+
+// Um trecho de código que o REPL já executou. Os spans dos tokens dele são deslocados de
+// `offset` bytes, então cada span aponta para um único trecho
+struct Source {
+    name: String,
+    text: String,
+    offset: usize,
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        let output = SharedOutput::new();
+        Repl {
+            interpreter: Interpreter::with_output(Box::new(output.clone())),
+            pending: String::new(),
+            output,
+            overflow_mode: OverflowMode::default(),
+            sources: Vec::new(),
+        }
+    }
+
+    // Escolhe o que acontece quando uma conta entre inteiros estoura, valendo também depois de um
+    // :reset
+    pub fn set_overflow_mode(&mut self, mode: OverflowMode) {
+        self.overflow_mode = mode;
+        self.interpreter.set_overflow_mode(mode);
+    }
+
+    // O prompt muda quando a entrada atual continua na próxima linha
+    pub fn prompt(&self) -> &'static str {
+        if self.pending.is_empty() {
+            "mc> "
+        } else {
+            "... "
+        }
+    }
+
+    // Processa uma linha digitada, escrevendo resultados e erros em `out`. Devolve false quando
+    // o usuário pediu para sair
+    pub fn feed_line(&mut self, line: &str, out: &mut dyn Write) -> io::Result<bool> {
+        if self.pending.is_empty() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                return Ok(true);
+            }
+            if let Some(command) = trimmed.strip_prefix(':') {
+                return self.run_command(command, out);
+            }
+        }
+
+        self.pending.push_str(line);
+        self.pending.push('\n');
+
+        if !input_is_complete(&self.pending) {
+            return Ok(true);
+        }
+
+        let source = std::mem::take(&mut self.pending);
+        self.eval_source(&source, out)?;
+        Ok(true)
+    }
+
+    fn run_command(&mut self, command: &str, out: &mut dyn Write) -> io::Result<bool> {
+        let (name, argument) = match command.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (command, ""),
+        };
+
+        match name {
+            "quit" | "q" => return Ok(false),
+            "help" => write!(out, "{}", AJUDA)?,
+            "reset" => {
+                self.interpreter = Interpreter::with_output(Box::new(self.output.clone()));
+                self.interpreter.set_overflow_mode(self.overflow_mode);
+                self.sources.clear();
+                writeln!(out, "estado do interpretador apagado")?;
+            }
+            "funcs" => {
+                for func in self.interpreter.functions() {
+                    let params: Vec<&str> = func.params.iter().map(|p| p.name.as_str()).collect();
+                    writeln!(out, "{}({})", func.name, params.join(", "))?;
+                }
+            }
+            "tokens" => {
+                if let Some(tokens) = tokenize(argument, out)? {
+                    for token in &tokens {
+                        writeln!(out, "{}:{}\t{}", token.span.line, token.span.column, token.token)?;
+                    }
+                }
+            }
+            "ast" => {
+                if let Some(inputs) = parse(argument, 0, &self.interpreter, out)? {
+                    for input in &inputs {
+                        writeln!(out, "{:#?}", input)?;
+                    }
+                }
+            }
+            "load" => self.load_file(argument, out)?,
+            _ => writeln!(out, "comando desconhecido ':{}', use :help para ver os comandos", name)?,
+        }
+
+        Ok(true)
+    }
+
+    fn load_file(&mut self, path: &str, out: &mut dyn Write) -> io::Result<()> {
+        let source = match std::fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => return writeln!(out, "erro: não foi possível ler '{}': {}", path, e),
+        };
+
+        let tokens = match Lexer::nova_instancia(&source).tokenizador() {
+            Ok(tokens) => tokens,
+            Err(errors) => {
+                for error in &errors {
+                    write!(out, "{}", Diagnostic::from(error).render(path, &source, false))?;
+                }
+                return Ok(());
+            }
+        };
+
+        let offset = self.next_offset();
+        let program = match Parser::new(shift_tokens(tokens, offset)).parse() {
+            Ok(program) => program,
+            Err(errors) => {
+                for error in &errors {
+                    write!(out, "{}", unshifted(Diagnostic::from(error), offset).render(path, &source, false))?;
+                }
+                return Ok(());
+            }
+        };
+        self.sources.push(Source { name: path.to_string(), text: source, offset });
+
+        let count = program.functions().count();
+        let result = self.interpreter.reload(program);
+        self.write_program_output(out)?;
+        match result {
+            Ok(()) => writeln!(out, "{} função(ões) carregada(s) de '{}'", count, path),
+            Err(e) => self.write_runtime_error(&e, out),
+        }
+    }
+
+    fn eval_source(&mut self, source: &str, out: &mut dyn Write) -> io::Result<()> {
+        let offset = self.next_offset();
+        let inputs = match parse(source, offset, &self.interpreter, out)? {
+            Some(inputs) => inputs,
+            None => return Ok(()),
+        };
+        self.sources.push(Source { name: NOME_ENTRADA.to_string(), text: source.to_string(), offset });
+
+        for input in inputs {
+            let result = match &input {
+                ReplInput::Function(func) => {
+                    self.interpreter.define_function(func.clone());
+                    continue;
+                }
+                ReplInput::Struct(def) => {
+                    self.interpreter.define_struct(def.clone());
+                    continue;
+                }
+                ReplInput::Enum(def) => {
+                    self.interpreter.define_enum(def.clone());
+                    continue;
+                }
+                ReplInput::Stmt(stmt) => self.interpreter.run_statement(stmt).map(|_| None),
+                ReplInput::Expr(expr) => self.interpreter.evaluate(expr).map(Some),
+            };
+            self.write_program_output(out)?;
+
+            match result {
+                // chamadas como println(...) não têm valor para mostrar
+                Ok(Some(Value::Void)) | Ok(None) => {}
+                Ok(Some(value)) => writeln!(out, "{}", value)?,
+                Err(e) => {
+                    self.write_runtime_error(&e, out)?;
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Repassa o que o programa imprimiu, antes do valor ou do erro da entrada
+    fn write_program_output(&self, out: &mut dyn Write) -> io::Result<()> {
+        out.write_all(&self.output.take())
+    }
+
+    // O deslocamento da próxima entrada: logo depois do fim da última, para que os spans de duas
+    // entradas nunca se confundam
+    fn next_offset(&self) -> usize {
+        self.sources.last().map_or(0, |source| source.offset + source.text.len() + 1)
+    }
+
+    // Mostra um erro de execução no código de onde ele veio, que pode ser uma entrada anterior (a
+    // definição de uma função chamada agora, por exemplo) ou um arquivo carregado com :load
+    fn write_runtime_error(&self, error: &RuntimeError, out: &mut dyn Write) -> io::Result<()> {
+        let diagnostic = Diagnostic::from_runtime_error(error, &self.interpreter);
+        let start = diagnostic.span.start;
+        match self.sources.iter().rev().find(|source| start >= source.offset && start <= source.offset + source.text.len()) {
+            Some(source) => write!(out, "{}", unshifted(diagnostic, source.offset).render(&source.name, &source.text, false)),
+            None => write!(out, "{}", diagnostic.render(NOME_ENTRADA, "", false)),
+        }
+    }
+}
+
+fn shift_tokens(tokens: Vec<SpannedToken>, offset: usize) -> Vec<SpannedToken> {
+    tokens
+        .into_iter()
+        .map(|token| SpannedToken { span: token.span.shifted(offset as isize), ..token })
+        .collect()
+}
+
+// O diagnóstico com o span de volta às posições dentro do próprio trecho de código
+fn unshifted(mut diagnostic: Diagnostic, offset: usize) -> Diagnostic {
+    diagnostic.span = diagnostic.span.shifted(-(offset as isize));
+    diagnostic
+}
+
+// A entrada está completa quando não sobrou chave, parêntese ou comentário de bloco aberto
+fn input_is_complete(source: &str) -> bool {
+    let tokens = match Lexer::nova_instancia(source).tokenizador() {
+        Ok(tokens) => tokens,
+        Err(errors) => {
+            return !errors
+                .iter()
+                .any(|e| e.kind == LexErrorKind::UnterminatedComment);
+        }
+    };
+
+    let mut depth: i64 = 0;
+    for token in &tokens {
+        match token.token {
+            Token::AbreChave | Token::AbrePar => depth += 1,
+            Token::FechaChave | Token::FechaPar => depth -= 1,
+            _ => {}
+        }
+    }
+    depth <= 0
+}
+
+fn tokenize(source: &str, out: &mut dyn Write) -> io::Result<Option<Vec<SpannedToken>>> {
+    match Lexer::nova_instancia(source).tokenizador() {
+        Ok(tokens) => Ok(Some(tokens)),
+        Err(errors) => {
+            for error in &errors {
+                write!(out, "{}", Diagnostic::from(error).render(NOME_ENTRADA, source, false))?;
+            }
+            Ok(None)
+        }
+    }
+}
+
+// Analisa uma entrada do REPL, com os spans deslocados de `offset` bytes. As constantes de enum
+// definidas nas entradas anteriores valem também nos valores dos case
+fn parse(source: &str, offset: usize, interpreter: &Interpreter, out: &mut dyn Write) -> io::Result<Option<Vec<ReplInput>>> {
+    let tokens = match tokenize(source, out)? {
+        Some(tokens) => tokens,
+        None => return Ok(None),
+    };
+
+    let mut parser = Parser::new(shift_tokens(tokens, offset));
+    parser.define_constants(interpreter.enum_constants());
+    match parser.parse_repl() {
+        Ok(inputs) => Ok(Some(inputs)),
+        Err(errors) => {
+            for error in &errors {
+                write!(out, "{}", unshifted(Diagnostic::from(error), offset).render(NOME_ENTRADA, source, false))?;
+            }
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,54 @@
+// O span marca um trecho do código-fonte: os deslocamentos em bytes de início e fim (o fim é
+// exclusivo) e a linha e coluna onde o trecho começa, ambas contadas a partir de 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+        Span { start, end, line, column }
+    }
+
+    // Junta dois spans num só, que vai do início deste até o fim do outro
+    pub fn to(self, other: Span) -> Span {
+        if other.start < self.start {
+            return other.to(self);
+        }
+        Span {
+            start: self.start,
+            end: self.end.max(other.end),
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    // O mesmo trecho com os deslocamentos em bytes somados de `offset` (que pode ser negativo), para
+    // quando o código faz parte de um texto maior, como as entradas do REPL
+    pub fn shifted(self, offset: isize) -> Span {
+        Span {
+            start: self.start.saturating_add_signed(offset),
+            end: self.end.saturating_add_signed(offset),
+            ..self
+        }
+    }
+
+    // Um span vazio logo depois do fim deste, útil para apontar "o arquivo acabou aqui"
+    pub fn after(self) -> Span {
+        Span {
+            start: self.end,
+            end: self.end,
+            line: self.line,
+            column: self.column + (self.end - self.start),
+        }
+    }
+}
+
+impl std::fmt::Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "linha {}, coluna {}", self.line, self.column)
+    }
+}
@@ -0,0 +1,103 @@
+use mini_interpretador::{Lexer, Parser, Interpreter, Value, RuntimeError, SharedOutput};
+
+//executa o programa capturando o que ele imprime
+fn run_capturing(input: &str) -> (Result<Value, RuntimeError>, String) {
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    let output = SharedOutput::new();
+    let mut interpreter = Interpreter::with_output(Box::new(output.clone()));
+    let result = interpreter.interpret(program);
+    (result, output.contents())
+}
+
+#[test]
+fn test_print_e_println() {
+  //print separa os argumentos por espaço e println termina a linha
+    let input = r#"
+        int main() {
+            print("a", 1, true);
+            println();
+            println("fim", 2 + 3);
+            return 0;
+        }
+    "#;
+
+    let (result, output) = run_capturing(input);
+    assert!(result.is_ok());
+    assert_eq!(output, "a 1 true\nfim 5\n");
+}
+
+#[test]
+fn test_printf_com_formatos() {
+  //%d, %b, %s, %f e %% são substituídos, e printf devolve quantos bytes escreveu
+    let input = r#"
+        int main() {
+            int x = 42;
+            return printf("x=%d ok=%b %s 100%% %f %.2f", x, x > 0, "fim", 0.5, 2);
+        }
+    "#;
+
+    let (result, output) = run_capturing(input);
+    assert_eq!(output, "x=42 ok=true fim 100% 0.500000 2.00");
+    assert!(matches!(result, Ok(Value::Number(35))));
+}
+
+#[test]
+fn test_printf_argumentos_errados() {
+  //formato sem argumento suficiente ou com tipo errado é erro de execução, e nada é impresso
+    let (result, output) = run_capturing(r#"int main() { printf("%d %d", 1); return 0; }"#);
+    assert!(matches!(result, Err(RuntimeError::BuiltinError(..))));
+    assert_eq!(output, "");
+
+    let (result, _) = run_capturing(r#"int main() { printf("%b", "texto"); return 0; }"#);
+    let error = result.unwrap_err();
+    assert_eq!(error.to_string(), "Erro: Na função 'printf': '%b' não aceita o valor texto");
+}
+
+#[test]
+fn test_embutidas_antes_das_do_usuario() {
+  //uma função do usuário com o nome de uma embutida não a substitui
+    let input = r#"
+        int println(int x) { return x; }
+        int main() {
+            println(7);
+            return 0;
+        }
+    "#;
+
+    let (_, output) = run_capturing(input);
+    assert_eq!(output, "7\n");
+}
+
+#[test]
+fn test_funcoes_de_texto() {
+  //len, substr e char_at contam caracteres, não bytes, e to_int/to_string convertem
+    let input = r#"
+        int main() {
+            string s = "ação";
+            printf("%d %s %c|", len(s), substr(s, 1, 10), char_at(s, 2));
+            println(to_int(" 42 ") + 1, to_string(7) + "!");
+            return 0;
+        }
+    "#;
+
+    let (result, output) = run_capturing(input);
+    assert!(result.is_ok());
+    assert_eq!(output, "4 ção ã|43 7!\n");
+}
+
+#[test]
+fn test_funcoes_de_texto_com_erro() {
+  //posição fora do texto, conversão inválida e tipo errado são erros de execução
+    let (result, _) = run_capturing(r#"int main() { return len(char_at("abc", 3)); }"#);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "Erro: Na função 'char_at': a posição 3 está fora do texto de tamanho 3"
+    );
+
+    let (result, _) = run_capturing(r#"int main() { return to_int("12a"); }"#);
+    assert!(matches!(result, Err(RuntimeError::BuiltinError(..))));
+
+    let (result, _) = run_capturing("int main() { return len(5); }");
+    assert!(matches!(result, Err(RuntimeError::TypeError(..))));
+}
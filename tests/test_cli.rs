@@ -0,0 +1,124 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+//roda o binário passando o programa pela entrada padrão
+fn rodar_cli(args: &[&str], programa: &str) -> Output {
+    let mut filho = Command::new(env!("CARGO_BIN_EXE_mini_interpretador"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("não foi possível iniciar o binário");
+
+    filho.stdin.take().unwrap().write_all(programa.as_bytes()).unwrap();
+    filho.wait_with_output().unwrap()
+}
+
+#[test]
+fn test_cli_run_quiet_com_argumentos() {
+//no modo quiet só o resultado é impresso, e os argumentos vão para os parâmetros da main
+    let saida = rodar_cli(&["--quiet", "run", "-", "4", "5"], "int main(int a, int b) { return a * b; }");
+
+    assert_eq!(String::from_utf8_lossy(&saida.stdout), "20\n");
+    assert_eq!(saida.status.code(), Some(20));
+}
+
+#[test]
+fn test_cli_check_e_tokens() {
+//check só analisa o programa, e tokens lista cada token com a posição
+    let saida = rodar_cli(&["check", "-"], "int main() { return 0; }");
+    assert!(saida.status.success());
+    assert_eq!(String::from_utf8_lossy(&saida.stdout), "<stdin>: nenhum erro encontrado\n");
+
+    let saida = rodar_cli(&["tokens", "-"], "int x;");
+    assert_eq!(String::from_utf8_lossy(&saida.stdout), "1:1\t'int'\n1:5\tidentificador 'x'\n1:6\t';'\n");
+}
+
+#[test]
+fn test_cli_erro_de_sintaxe() {
+//um erro de sintaxe é mostrado com o nome do arquivo e a posição, e o processo falha
+    let saida = rodar_cli(&["check", "-"], "int main() { return 1 }");
+
+    assert!(!saida.status.success());
+    let erro = String::from_utf8_lossy(&saida.stderr);
+    assert!(erro.contains("esperado ';'"));
+    assert!(erro.contains("<stdin>:1:23"));
+}
+
+#[test]
+fn test_cli_uso_invalido() {
+//comando desconhecido mostra o uso
+    let saida = rodar_cli(&["compila", "-"], "");
+
+    assert_eq!(saida.status.code(), Some(64));
+    assert!(String::from_utf8_lossy(&saida.stderr).contains("Uso: mini_interpretador"));
+}
+
+#[test]
+fn test_cli_codigo_de_saida_como_em_c() {
+//o código de saída é o valor da main com só os 8 bits mais baixos, como em C
+    let saida = rodar_cli(&["-q", "run", "-"], "int main() { return 300; }");
+    assert_eq!(saida.status.code(), Some(44));
+
+    let saida = rodar_cli(&["-q", "run", "-"], "int main() { return -1; }");
+    assert_eq!(saida.status.code(), Some(255));
+
+    let saida = rodar_cli(&["-q", "run", "-"], "int main() { return 256; }");
+    assert_eq!(saida.status.code(), Some(0));
+}
+
+#[test]
+fn test_cli_codigos_de_falha_distintos() {
+//erros léxicos, sintáticos e de execução têm códigos de saída diferentes
+    let saida = rodar_cli(&["run", "-"], "int main() { return 1 # 2; }");
+    assert_eq!(saida.status.code(), Some(65));
+
+    let saida = rodar_cli(&["run", "-"], "int main() { return 1 }");
+    assert_eq!(saida.status.code(), Some(66));
+
+    let saida = rodar_cli(&["run", "-"], "int main() { return 1 / 0; }");
+    assert_eq!(saida.status.code(), Some(70));
+}
+
+#[test]
+fn test_cli_relatorio_detalhado() {
+//o modo verbose mantém o relatório antigo, com tokens, AST e resultado
+    let saida = rodar_cli(&["--verbose", "run", "-"], "int main() { return 7; }");
+    let texto = String::from_utf8_lossy(&saida.stdout);
+
+    assert!(texto.contains("Estes são os tokens"));
+    assert!(texto.contains("Return"));
+    assert!(texto.contains("Programa executado com sucesso!"));
+    assert!(texto.ends_with("Resultado: 7\n"));
+    assert_eq!(saida.status.code(), Some(7));
+}
+
+#[test]
+fn test_cli_modo_de_estouro() {
+//--overflow escolhe o que acontece quando uma conta inteira estoura
+    let programa = "int main() { int max = 9223372036854775807; println(max + 1); return 0; }";
+
+    let saida = rodar_cli(&["run", "-"], programa);
+    assert_eq!(saida.status.code(), Some(70));
+    assert!(String::from_utf8_lossy(&saida.stderr).contains("Estouro de inteiro"));
+
+    let saida = rodar_cli(&["--quiet", "--overflow=wrap", "run", "-"], programa);
+    assert_eq!(String::from_utf8_lossy(&saida.stdout), "-9223372036854775808\n0\n");
+
+    //o processo sai antes de ler a entrada, então ela fica vazia
+    let saida = rodar_cli(&["--overflow=grande", "run", "-"], "");
+    assert_eq!(saida.status.code(), Some(64));
+}
+
+#[test]
+fn test_cli_modo_de_estouro_no_repl() {
+//--overflow também vale para o REPL, inclusive depois de um :reset
+    let entrada = "int m = 9223372036854775807;\nm + 1\n:reset\nint n = -9223372036854775807;\nn - 2\n";
+    let saida = rodar_cli(&["--overflow=saturate", "repl"], entrada);
+
+    assert_eq!(
+        String::from_utf8_lossy(&saida.stdout),
+        "9223372036854775807\nestado do interpretador apagado\n-9223372036854775808\n"
+    );
+}
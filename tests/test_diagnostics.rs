@@ -0,0 +1,92 @@
+use mini_interpretador::{Lexer, Parser, Interpreter, Diagnostic};
+use mini_interpretador::diagnostics::{edit_distance, suggest};
+
+#[test]
+fn test_distancia_de_edicao() {
+//verifica a distância de edição em casos conhecidos, contando inversões como um erro só
+    assert_eq!(edit_distance("soma", "soma"), 0);
+    assert_eq!(edit_distance("somaa", "soma"), 1);
+    assert_eq!(edit_distance("smoa", "soma"), 1);
+    assert_eq!(edit_distance("kitten", "sitting"), 3);
+    assert_eq!(edit_distance("", "abc"), 3);
+}
+
+#[test]
+fn test_sugestao_de_nome() {
+//só sugere nomes realmente parecidos
+    let nomes = ["soma", "multiplica", "main"];
+    assert_eq!(suggest("smoa", nomes), Some("soma"));
+    assert_eq!(suggest("multiplca", nomes), Some("multiplica"));
+    assert_eq!(suggest("xyz", nomes), None);
+}
+
+#[test]
+fn test_renderiza_erro_de_sintaxe() {
+//o erro de sintaxe mostra arquivo, linha:coluna, a linha do código e o sublinhado
+    let input = "int main() {\n    int x = 1\n    return x;\n}";
+    let mut lexer = Lexer::nova_instancia(input);
+    let mut parser = Parser::new(lexer.tokenizador().unwrap());
+    let errors = parser.parse().unwrap_err();
+
+    let texto = Diagnostic::from(&errors[0]).render("teste.mc", input, false);
+    let esperado = "\
+erro: erro de sintaxe, esperado ';', mas encontrado 'return'
+ --> teste.mc:3:5
+  |
+3 |     return x;
+  |     ^^^^^^
+";
+    assert_eq!(texto, esperado);
+}
+
+#[test]
+fn test_renderiza_linha_com_tabulacao() {
+//o recuo do sublinhado repete as tabulações da linha, para o '^' ficar embaixo do trecho
+    let input = "int main() {\n\tint x = 1\n\treturn x;\n}";
+    let mut parser = Parser::new(Lexer::nova_instancia(input).tokenizador().unwrap());
+    let errors = parser.parse().unwrap_err();
+
+    let texto = Diagnostic::from(&errors[0]).render("teste.mc", input, false);
+    assert!(texto.contains("3 | \treturn x;\n  | \t^^^^^^\n"));
+}
+
+#[test]
+fn test_renderiza_erro_de_execucao_com_dica() {
+//uma função com nome errado ganha a dica com o nome mais parecido
+    let input = "int soma(int a, int b) { return a + b; }\nint main() { return smoa(1, 2); }";
+    let mut lexer = Lexer::nova_instancia(input);
+    let mut parser = Parser::new(lexer.tokenizador().unwrap());
+    let program = parser.parse().unwrap();
+    let mut interpreter = Interpreter::new();
+    let error = interpreter.interpret(program).unwrap_err();
+
+    let diagnostico = Diagnostic::from_runtime_error(&error, &interpreter);
+    assert_eq!(diagnostico.help.as_deref(), Some("você quis dizer `soma`?"));
+
+    let texto = diagnostico.render("teste.mc", input, false);
+    assert!(texto.contains(" --> teste.mc:2:21\n"));
+    assert!(texto.contains("2 | int main() { return smoa(1, 2); }\n"));
+    assert!(texto.contains("  |                     ^^^^^^^^^^\n"));
+    assert!(texto.ends_with("  = ajuda: você quis dizer `soma`?\n"));
+}
+
+#[test]
+fn test_sugestao_de_variavel_local() {
+//um nome errado de variável local ganha a dica com a local mais parecida, mesmo depois de a função terminar
+    let input = "int conta() { int contador = 1; return contadr + 1; }\nint main() { return conta(); }";
+    let mut parser = Parser::new(Lexer::nova_instancia(input).tokenizador().unwrap());
+    let program = parser.parse().unwrap();
+    let mut interpreter = Interpreter::new();
+    let error = interpreter.interpret(program).unwrap_err();
+
+    let diagnostico = Diagnostic::from_runtime_error(&error, &interpreter);
+    assert_eq!(diagnostico.help.as_deref(), Some("você quis dizer `contador`?"));
+}
+
+#[test]
+fn test_renderiza_com_cores() {
+//no modo colorido aparecem os códigos ANSI; no modo texto, não
+    let diagnostico = Diagnostic::new("teste", mini_interpretador::Span::new(0, 3, 1, 1));
+    assert!(diagnostico.render("a.mc", "int", true).contains("\x1b[1;31m"));
+    assert!(!diagnostico.render("a.mc", "int", false).contains('\x1b'));
+}
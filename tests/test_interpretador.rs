@@ -1,13 +1,13 @@
-use mini_interpretador::{Lexer, Parser, Interpreter, Value};
+use mini_interpretador::{Lexer, Parser, Interpreter, OverflowMode, SharedOutput, Value, RuntimeError, Expr, ExprKind, Span};
 
 //função auxiliar que executa todo o pipeline do lexer ao interpretador
 fn run_program(input: &str) -> Result<Value, Box<dyn std::error::Error>> {
     let mut lexer = Lexer::nova_instancia(input);
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     let mut parser = Parser::new(tokens);
-    let (functions, main_body) = parser.parse();
+    let program = parser.parse().unwrap();
     let mut interpreter = Interpreter::new();
-    Ok(interpreter.interpret(functions, main_body)?)
+    Ok(interpreter.interpret(program)?)
 }
 
 #[test]
@@ -79,4 +79,931 @@ fn test_execucao_condicional() {
     } else {
         panic!("Expected number result");
     }
-}
\ No newline at end of file
+}
+#[test]
+fn test_atribuicao_em_laco_while() {
+  //verifica se a atribuição atualiza o contador e o laço termina
+    let input = r#"
+        int main() {
+            int i = 0;
+            int soma = 0;
+            while (i < 5) {
+                soma = soma + i;
+                i = i + 1;
+            }
+            return soma;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    if let Value::Number(n) = result {
+        assert_eq!(n, 10);
+    } else {
+        panic!("Expected number result");
+    }
+}
+
+#[test]
+fn test_atribuicao_em_laco_for() {
+  //verifica a atualização do for com atribuição e a associatividade à direita
+    let input = r#"
+        int main() {
+            int a = 0;
+            int b = 0;
+            int total = 0;
+            for (int i = 0; i < 4; i = i + 1) {
+                total = total + i;
+            }
+            a = b = total;
+            return a + b;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    if let Value::Number(n) = result {
+        assert_eq!(n, 12);
+    } else {
+        panic!("Expected number result");
+    }
+}
+
+#[test]
+fn test_atribuicao_variavel_nao_declarada() {
+  //atribuir a uma variável nunca declarada deve gerar erro
+    let mut lexer = Lexer::nova_instancia("int main() { x = 1; return x; }");
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+    let mut interpreter = Interpreter::new();
+
+    match interpreter.interpret(program) {
+        Err(RuntimeError::UndefinedVariable(name, _, _)) => assert_eq!(name, "x"),
+        other => panic!("Era esperado UndefinedVariable, mas obtivemos: {:?}", other),
+    }
+}
+
+#[test]
+fn test_atribuicoes_compostas() {
+  //verifica +=, -=, *=, /= e %= em sequência
+    let input = r#"
+        int main() {
+            int x = 10;
+            x += 5;
+            x -= 3;
+            x *= 4;
+            x /= 6;
+            x %= 5;
+            return x;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    if let Value::Number(n) = result {
+        assert_eq!(n, 3); // ((10 + 5 - 3) * 4 / 6) % 5
+    } else {
+        panic!("Expected number result");
+    }
+}
+
+#[test]
+fn test_incremento_prefixado_e_posfixado() {
+  //o pós-fixado devolve o valor antigo e o prefixado devolve o novo
+    let input = r#"
+        int main() {
+            int total = 0;
+            for (int i = 0; i < 3; i++) {
+                total += i;
+            }
+            int a = 5;
+            int b = a++;
+            int c = --a;
+            return total * 100 + b * 10 + c;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    if let Value::Number(n) = result {
+        assert_eq!(n, 355);
+    } else {
+        panic!("Expected number result");
+    }
+}
+
+#[test]
+fn test_variavel_do_for_nao_vaza() {
+  //a variável declarada na inicialização do for só existe dentro do laço
+    let input = r#"
+        int main() {
+            int total = 0;
+            for (int i = 0; i < 3; i++) {
+                total += i;
+            }
+            return i;
+        }
+    "#;
+
+    match run_program(input) {
+        Err(e) => assert_eq!(e.to_string(), "Erro: Variável 'i' não definida"),
+        Ok(v) => panic!("Era esperado erro, mas obtivemos: {:?}", v),
+    }
+}
+
+#[test]
+fn test_laco_for_reusa_nome_da_variavel() {
+  //dois for seguidos podem declarar o mesmo contador, e o corpo ganha um escopo novo a cada volta
+    let input = r#"
+        int main() {
+            int total = 0;
+            for (int i = 0; i < 3; i++) {
+                int dobro = i * 2;
+                total += dobro;
+            }
+            for (int i = 0; i < 2; i++) {
+                total += 100;
+            }
+            return total;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    if let Value::Number(n) = result {
+        assert_eq!(n, 206);
+    } else {
+        panic!("Expected number result");
+    }
+}
+
+#[test]
+fn test_sombreamento_em_bloco_interno() {
+  //um bloco interno pode sombrear a variável de fora sem alterá-la
+    let input = r#"
+        int main() {
+            int x = 1;
+            if (true) {
+                int x = 50;
+                x += 1;
+            }
+            return x;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    if let Value::Number(n) = result {
+        assert_eq!(n, 1);
+    } else {
+        panic!("Expected number result");
+    }
+}
+
+#[test]
+fn test_redeclaracao_no_mesmo_escopo() {
+  //declarar duas vezes no mesmo escopo é erro
+    let input = "int main() { int x = 1; int x = 2; return x; }";
+
+    match run_program(input) {
+        Err(e) => assert_eq!(e.to_string(), "Erro: Variável 'x' já declarada neste escopo"),
+        Ok(v) => panic!("Era esperado erro, mas obtivemos: {:?}", v),
+    }
+}
+
+#[test]
+fn test_return_dentro_de_laco_encerra_funcao() {
+  //um return dentro de um if num laço deve sair da função, e não só do bloco
+    let input = r#"
+        int primeiro_multiplo(int n, int limite) {
+            for (int i = 1; i < limite; i++) {
+                if (i * n > 20) {
+                    return i * n;
+                }
+            }
+            return 0;
+        }
+        int main() {
+            return primeiro_multiplo(7, 100);
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    if let Value::Number(n) = result {
+        assert_eq!(n, 21);
+    } else {
+        panic!("Expected number result");
+    }
+}
+
+#[test]
+fn test_variaveis_globais() {
+  //globais são avaliadas antes da main, vistas por todas as funções e podem ser alteradas
+    let input = r#"
+        int contador = 0;
+        int passo = contador + 2;
+
+        int incrementa() {
+            contador += passo;
+            return contador;
+        }
+
+        int main() {
+            incrementa();
+            incrementa();
+            return contador;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    if let Value::Number(n) = result {
+        assert_eq!(n, 4);
+    } else {
+        panic!("Expected number result");
+    }
+}
+
+#[test]
+fn test_local_sombreia_global() {
+  //uma variável local com o mesmo nome esconde a global sem alterá-la
+    let input = r#"
+        int x = 7;
+
+        int le_global() {
+            return x;
+        }
+
+        int main() {
+            int x = 1;
+            x = 100;
+            return le_global() + x;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    if let Value::Number(n) = result {
+        assert_eq!(n, 107);
+    } else {
+        panic!("Expected number result");
+    }
+}
+
+#[test]
+fn test_erro_de_execucao_com_posicao() {
+  //o erro de execução informa o trecho da expressão que falhou
+    let input = "int main() {\n    int zero = 0;\n    return 10 / zero;\n}";
+    let mut lexer = Lexer::nova_instancia(input);
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+    let mut interpreter = Interpreter::new();
+
+    let error = interpreter.interpret(program).unwrap_err();
+    assert!(matches!(error, RuntimeError::DivisionByZero(_)));
+    let span = error.span();
+    assert_eq!((span.line, span.column), (3, 12));
+    assert_eq!(&input[span.start..span.end], "10 / zero");
+}
+
+#[test]
+fn test_erro_de_conversao_no_return_com_posicao() {
+  //um valor de return que não combina com o tipo da função aponta para a expressão do return
+    let input = "int main() {\n    string s = \"abc\";\n    return s;\n}";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let error = Interpreter::new().interpret(program).unwrap_err();
+    assert!(matches!(error, RuntimeError::TypeError(..)));
+    let span = error.span();
+    assert_eq!((span.line, span.column), (3, 12));
+    assert_eq!(&input[span.start..span.end], "s");
+}
+
+#[test]
+fn test_textos_concatenacao_e_comparacao() {
+  //+ concatena textos, == e < comparam, e o texto vazio é falso
+    let input = r#"
+        string saudacao(string nome) {
+            return "Olá, " + nome + "!";
+        }
+        int main() {
+            string s = saudacao("Ana");
+            s += " :)";
+            if (s == "Olá, Ana! :)" && "abc" < "abd" && !("b" < "a") && !"") {
+                return 1;
+            }
+            return 0;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(1)));
+}
+
+#[test]
+fn test_erro_de_tipo_em_operacao() {
+  //misturar texto e int numa operação é erro de execução, e não pânico
+    let error = run_program(r#"int main() { return "a" + 1; }"#).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Erro: Tipo incompatível: o operador '+' não pode ser usado entre string e int"
+    );
+
+    let error = run_program("int main() { return -true; }").unwrap_err();
+    assert!(error.to_string().contains("o operador '-' não pode ser usado com bool"));
+}
+
+#[test]
+fn test_conversoes_aritmeticas_com_double() {
+  //int com double vira double, divisão entre ints trunca e as variáveis mantêm o tipo declarado
+    let input = r#"
+        double media(int a, int b) {
+            return (a + b) / 2.0;
+        }
+        int main() {
+            int inteiro = 7 / 2;
+            double real = 7 / 2;
+            double m = media(3, 4);
+            int truncado = m;
+            truncado += 0.9;
+            if (inteiro == 3 && real == 3.0 && m == 3.5 && truncado == 3 && 1 < 1.5) {
+                return (int)(m * 10);
+            }
+            return 0;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(35)));
+}
+
+#[test]
+fn test_raiz_quadrada_por_newton() {
+  //o exemplo que motivou o double: raiz quadrada pelo método de Newton
+    let input = r#"
+        double raiz(double x) {
+            double r = x;
+            for (int i = 0; i < 20; i++) {
+                r = (r + x / r) / 2;
+            }
+            return r;
+        }
+        double main() {
+            return raiz(2);
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    let Value::Float(r) = result else {
+        panic!("Expected double result");
+    };
+    assert!((r - 2f64.sqrt()).abs() < 1e-12);
+    assert_eq!(Value::Float(1.0).to_string(), "1.0");
+}
+
+#[test]
+fn test_conversao_explicita_invalida() {
+  //texto não pode ser convertido para int com cast, e % não existe para double
+    let error = run_program(r#"int main() { return (int)"12"; }"#).unwrap_err();
+    assert!(error.to_string().contains("não é possível converter string para int"));
+
+    let error = run_program("int main() { double x = 5.5; x %= 2; return 0; }").unwrap_err();
+    assert!(error.to_string().contains("o operador '%' não pode ser usado entre double e int"));
+}
+
+#[test]
+fn test_conversao_implicita_invalida() {
+  //o tipo declarado vale em toda atribuição: texto não vira número nem bool, nem o contrário
+    let error = run_program(r#"int main() { int x = "abc"; return x; }"#).unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::TypeError(..))));
+    assert!(error.to_string().contains("não é possível converter string para int"));
+
+    let error = run_program(r#"int main() { string s = "a"; s = 5; return 0; }"#).unwrap_err();
+    assert!(error.to_string().contains("não é possível converter int para string"));
+
+    let error = run_program(r#"int main() { bool b = true; b = "sim"; return 0; }"#).unwrap_err();
+    assert!(error.to_string().contains("não é possível converter string para bool"));
+}
+
+#[test]
+fn test_bool_como_zero_ou_um() {
+  //como em C, o resultado de uma comparação vale 0 ou 1 num int, e um número vira bool por ser diferente de zero
+    let input = r#"
+        int par(int n) { return n % 2 == 0; }
+        int main() {
+            int a = 2;
+            int b = 5;
+            int c = a < b;
+            c += (a > b);
+            bool flag = 7;
+            flag = !flag;
+            int mascara = a & b == 5;
+            return par(4) * 1000 + par(3) * 100 + c * 10 + flag + mascara;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(1010)));
+}
+
+#[test]
+fn test_cifra_de_cesar_com_char() {
+  //char vale o seu código nas contas, como em C, e volta a ser char ao ser guardado
+    let input = r#"
+        char desloca(char c, int n) {
+            if (c >= 'a' && c <= 'z') {
+                int k = c - 'a' + n;
+                while (k >= 26) {
+                    k -= 26;
+                }
+                return 'a' + k;
+            }
+            return c;
+        }
+        string cifra(string texto, int n) {
+            string resultado = "";
+            for (int i = 0; i < len(texto); i++) {
+                resultado = resultado + desloca(char_at(texto, i), n);
+            }
+            return resultado;
+        }
+        int main() {
+            char c = 'y';
+            c++;
+            if (cifra("ola, mundo", 3) == "rod, pxqgr" && c == 'z' && c == 122) {
+                return c - 'a';
+            }
+            return -1;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(25)));
+}
+
+#[test]
+fn test_codigo_de_caractere_invalido() {
+  //um int que não é código de nenhum caractere não pode virar char
+    let error = run_program("int main() { char c = -1; return 0; }").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Erro: Tipo incompatível: -1 não é o código de um caractere válido"
+    );
+}
+
+#[test]
+fn test_arrays_ordenacao() {
+  //arrays são passados por referência: a função ordena o array de quem chamou
+    let input = r#"
+        int ordena(int v[], int n) {
+            for (int i = 0; i < n; i++) {
+                for (int j = 0; j + 1 < n - i; j++) {
+                    if (v[j] > v[j + 1]) {
+                        int t = v[j];
+                        v[j] = v[j + 1];
+                        v[j + 1] = t;
+                    }
+                }
+            }
+            return 0;
+        }
+        int main() {
+            int v[] = {5, 3, 9, 1, 7};
+            int zeros[4];
+            double d[2] = {1};
+            ordena(v, len(v));
+            v[4] += 100;
+            zeros[3]++;
+            d[1] = 7 / 2;
+            if (zeros[0] == 0 && zeros[3] == 1 && d[0] == 1.0 && d[1] == 3.0) {
+                return v[0] * 10000 + v[1] * 1000 + v[2] * 100 + v[3] * 10 + v[4];
+            }
+            return -1;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(13679)));
+}
+
+#[test]
+fn test_indice_fora_dos_limites() {
+  //ler ou escrever fora do array é erro de execução, com o índice e o tamanho
+    let error = run_program("int main() { int v[3]; int i = 3; return v[i]; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::IndexOutOfBounds { index: 3, len: 3, .. })));
+    assert_eq!(error.to_string(), "Erro: Índice 3 fora dos limites do array de tamanho 3");
+
+    let error = run_program("int main() { int v[] = {1}; v[-1] = 0; return 0; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::IndexOutOfBounds { index: -1, len: 1, .. })));
+
+    let error = run_program("int main() { int v[2] = {1, 2, 3}; return 0; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::InvalidArray(..))));
+}
+
+#[test]
+fn test_array_grande_demais() {
+  //um tamanho enorme é um erro apontando a dimensão, em vez de o processo abortar sem memória
+    let error = run_program("int main() { int v[100000000000]; return 0; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::InvalidArray(..))));
+    assert_eq!(error.to_string(), "Erro: Array inválido: o array 'v' passaria do limite de 16777216 elementos");
+
+    let error = run_program("int main() { int m[10000][10000]; return 0; }").unwrap_err();
+    let span = error.downcast_ref::<RuntimeError>().unwrap().span();
+    assert_eq!((span.line, span.column), (1, 27));
+}
+
+#[test]
+fn test_multiplicacao_de_matrizes() {
+  //arrays de duas dimensões, com inicializadores aninhados e passados para funções
+    let input = r#"
+        int multiplica(int a[][3], int b[][2], int c[][2]) {
+            for (int i = 0; i < 2; i++) {
+                for (int j = 0; j < 2; j++) {
+                    for (int k = 0; k < 3; k++) {
+                        c[i][j] += a[i][k] * b[k][j];
+                    }
+                }
+            }
+            return 0;
+        }
+        int main() {
+            int a[2][3] = {{1, 2, 3}, {4, 5, 6}};
+            int b[][2] = {{7, 8}, {9, 10}, {11}};
+            int c[2][2];
+            multiplica(a, b, c);
+            // a linha que ficou faltando em b ({11}) tem o segundo elemento zerado
+            if (c[0][0] == 58 && c[0][1] == 28 && c[1][0] == 139 && c[1][1] == 82) {
+                return 1;
+            }
+            return 0;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(1)));
+}
+
+#[test]
+fn test_limites_em_todas_as_dimensoes() {
+  //cada dimensão confere o seu índice, e as linhas são arrays independentes
+    let input = "int main() { int m[2][3]; m[0][0] = 1; return m[1][0] + m[0][3]; }";
+    let error = run_program(input).unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::IndexOutOfBounds { index: 3, len: 3, .. })));
+
+    let error = run_program("int main() { int m[2][2] = {{1, 2, 3}}; return 0; }").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Erro: Array inválido: o array 'm' tem tamanho 2, mas o inicializador tem 3 valores"
+    );
+
+    let error = run_program("int main() { int m[2][2] = {1, 2}; return 0; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::InvalidArray(..))));
+}
+
+#[test]
+fn test_structs_sao_copiadas() {
+  //atribuir uma struct ou passá-la para uma função copia os campos, como em C
+    let input = r#"
+        struct Ponto { int x; int y; };
+        int zera(struct Ponto p) { p.x = 0; p.y = 0; return 0; }
+        int main() {
+            struct Ponto a = {1, 2};
+            struct Ponto b = a;
+            b.x = 10;
+            zera(a);
+            a.y += 5;
+            return a.x * 100 + a.y * 10 + b.x;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(180)));
+}
+
+#[test]
+fn test_struct_retornada_e_aninhada() {
+  //structs podem ser devolvidas por funções, ter structs como campos e ser elementos de arrays
+    let input = r#"
+        struct Ponto { double x; double y; };
+        struct Segmento { struct Ponto a; struct Ponto b; };
+        struct Ponto meio(struct Segmento s) {
+            struct Ponto m = {(s.a.x + s.b.x) / 2, (s.a.y + s.b.y) / 2};
+            return m;
+        }
+        int main() {
+            struct Segmento s = {{0, 0}, {4, 2}};
+            struct Ponto pontos[2];
+            pontos[1] = meio(s);
+            pontos[1].y *= 10;
+            s.b.x = 100;
+            return (int)(pontos[1].x * 100 + pontos[1].y) + (int)pontos[0].x;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(210)));
+}
+
+#[test]
+fn test_erros_de_struct() {
+  //campos que não existem, tipos de struct diferentes e structs não definidas
+    let input = "struct P { int x; }; int main() { struct P p; return p.z; }";
+    let error = run_program(input).unwrap_err();
+    assert_eq!(error.to_string(), "Erro: A struct 'P' não tem o campo 'z'");
+
+    let input = "struct P { int x; }; struct Q { int x; }; int main() { struct P p; struct Q q = p; return 0; }";
+    let error = run_program(input).unwrap_err();
+    assert_eq!(error.to_string(), "Erro: Tipo incompatível: não é possível converter struct P para struct Q");
+
+    let error = run_program("int main() { struct R r; return 0; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::UndefinedStruct(..))));
+
+    let error = run_program("struct P { int x; }; int main() { struct P p = {1, 2}; return 0; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::TypeError(..))));
+}
+
+#[test]
+fn test_atribuicao_a_alvo_invalido_na_ast() {
+  //uma AST montada à mão com um alvo que não guarda valor é um erro de execução, e não um pânico
+    let span = Span::default();
+    let call = Expr::new(ExprKind::Call { name: "f".to_string(), args: Vec::new() }, span);
+    let target = Expr::new(ExprKind::Field { object: Box::new(call), field: "x".to_string() }, span);
+    let assign = Expr::new(
+        ExprKind::Assign { target: Box::new(target), value: Box::new(Expr::new(ExprKind::Number(3), span)) },
+        span,
+    );
+
+    let mut interpreter = Interpreter::new();
+    let error = interpreter.evaluate(&assign).unwrap_err();
+    assert!(matches!(error, RuntimeError::TypeError(..)));
+}
+
+#[test]
+fn test_enum_como_estados() {
+  //as constantes de enum são ints visíveis em todo o programa, inclusive nas funções
+    let input = r#"
+        enum Estado { PARADO, ANDANDO, CORRENDO = 10 };
+        enum Estado proximo(enum Estado e) {
+            if (e == PARADO) { return ANDANDO; }
+            return CORRENDO;
+        }
+        int main() {
+            enum Estado e = PARADO;
+            int passos = 0;
+            while (e != CORRENDO) {
+                e = proximo(e);
+                passos++;
+            }
+            return e + passos;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(12)));
+}
+
+#[test]
+fn test_constante_de_enum_nao_pode_ser_alterada() {
+  //atribuir a uma constante de enum é um erro, e nenhuma global pode ter o nome de uma constante
+    let error = run_program("enum Cor { VERDE }; int main() { VERDE = 2; return 0; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::AssignToConstant(name, _)) if name == "VERDE"));
+    assert_eq!(error.to_string(), "Erro: 'VERDE' é uma constante de enum e não pode ser alterada");
+
+    let error = run_program("enum Cor { VERDE }; int VERDE = 1; int main() { return 0; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::Redeclaration(..))));
+}
+
+#[test]
+fn test_switch_com_fallthrough() {
+  //sem break, a execução continua nos cases seguintes; sem case que bata, vai para o default
+    let input = r#"
+        enum Opcao { SOMAR = 1, DOBRAR, ZERAR };
+        int aplica(int opcao, int n) {
+            switch (opcao) {
+                case SOMAR:
+                    n += 1;
+                case DOBRAR:
+                    n *= 2;
+                    break;
+                case ZERAR:
+                    return 0;
+                default:
+                    n = -1;
+            }
+            return n;
+        }
+        int main() {
+            return aplica(SOMAR, 5) * 1000 + aplica(DOBRAR, 5) * 10 + aplica(ZERAR, 5) + aplica(9, 5);
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(12099)));
+}
+
+#[test]
+fn test_switch_com_caracteres() {
+  //os cases aceitam caracteres, e o default pode aparecer antes dos outros cases
+    let input = r#"
+        int vogais(string s) {
+            int total = 0;
+            for (int i = 0; i < len(s); i++) {
+                switch (char_at(s, i)) {
+                    default:
+                        total += 0;
+                        break;
+                    case 'a': case 'e': case 'i': case 'o': case 'u':
+                        total++;
+                }
+            }
+            return total;
+        }
+        int main() { return vogais("programacao"); }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(5)));
+
+    let error = run_program("int main() { switch (1.5) { default: break; } return 0; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::TypeError(..))));
+}
+
+#[test]
+fn test_break_e_continue() {
+  //o break sai só do laço mais próximo, e o continue num for ainda executa a atualização
+    let input = r#"
+        int main() {
+            int soma = 0;
+            for (int i = 0; i < 10; i++) {
+                if (i / 2 * 2 != i) { continue; }
+                int j = 0;
+                while (1) {
+                    j++;
+                    if (j > i) { break; }
+                    soma++;
+                }
+            }
+            int n = 0;
+            while (n < 100) {
+                n++;
+                if (n < 50) { continue; }
+                break;
+            }
+            return soma * 1000 + n;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(20050)));
+}
+
+#[test]
+fn test_continue_dentro_de_switch() {
+  //dentro de um switch, o break encerra o switch e o continue vai para a próxima volta do laço
+    let input = r#"
+        int main() {
+            int total = 0;
+            for (int i = 0; i < 5; i++) {
+                switch (i) {
+                    case 1: continue;
+                    case 3: break;
+                    default: total += 10;
+                }
+                total++;
+            }
+            return total;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(34)));
+}
+
+#[test]
+fn test_do_while() {
+  //o corpo roda pelo menos uma vez, e o continue vai direto para a condição
+    let input = r#"
+        int main() {
+            int vezes = 0;
+            do { vezes++; } while (0);
+
+            int i = 0;
+            int pares = 0;
+            do {
+                i++;
+                if (i / 2 * 2 != i) { continue; }
+                if (i > 8) { break; }
+                pares++;
+            } while (i < 20);
+            return vezes * 1000 + pares * 10 + i;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(1050)));
+}
+
+#[test]
+fn test_operadores_bit_a_bit() {
+  //resto, operadores bit a bit e deslocamentos entre inteiros, incluindo constantes de enum
+    let input = r#"
+        enum Permissao { LER = 1 << 0, ESCREVER = 1 << 1, EXECUTAR = 1 << 2 };
+        int main() {
+            int p = LER | EXECUTAR;
+            int checks = 0;
+            if (p & EXECUTAR) { checks++; }
+            if (!(p & ESCREVER)) { checks++; }
+            if ((p ^ LER) == EXECUTAR) { checks++; }
+            if (~0 == -1 && -17 % 5 == -2 && -16 >> 2 == -4) { checks++; }
+            if ((1 << 40) >> 38 == 4 && 'a' % 10 == 7) { checks++; }
+            return checks;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(5)));
+}
+
+#[test]
+fn test_erros_de_resto_e_deslocamento() {
+  //resto por zero, deslocamento fora de 0 a 63 e operadores bit a bit com double
+    let error = run_program("int main() { int z = 0; return 7 % z; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::DivisionByZero(_))));
+
+    let error = run_program("int main() { int n = 64; return 1 << n; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::InvalidShift(64, _))));
+    assert_eq!(error.to_string(), "Erro: Deslocamento de 64 bits, mas só é possível deslocar de 0 a 63");
+
+    let error = run_program("int main() { return 1 >> -1; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::InvalidShift(-1, _))));
+
+    let error = run_program("int main() { return 1.5 & 1; }").unwrap_err();
+    assert_eq!(error.to_string(), "Erro: Tipo incompatível: o operador '&' não pode ser usado entre double e int");
+}
+
+#[test]
+fn test_operador_condicional() {
+  //só o lado escolhido é avaliado, e condicionais encadeados escolhem da esquerda para a direita
+    let input = r#"
+        int chamadas = 0;
+        int conta(int n) { chamadas++; return n; }
+        int sinal(int n) { return n > 0 ? 1 : n < 0 ? -1 : 0; }
+        int main() {
+            int zero = 0;
+            int seguro = zero != 0 ? 10 / zero : conta(7);
+            int maior = seguro > 5 ? conta(seguro) : conta(-1);
+            return sinal(-4) + sinal(0) * 10 + sinal(9) * 100 + maior * 1000 + chamadas * 10000;
+        }
+    "#;
+
+    let result = run_program(input).unwrap();
+    assert!(matches!(result, Value::Number(27099)));
+}
+
+#[test]
+fn test_estouro_de_inteiro_e_erro() {
+  //por padrão uma conta que não cabe em 64 bits é um erro, inclusive MIN / -1 e -MIN
+    let error = run_program("int main() { int max = 9223372036854775807; return max + 1; }").unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::IntegerOverflow(_, _))));
+    assert_eq!(
+        error.to_string(),
+        "Erro: Estouro de inteiro: o resultado de 9223372036854775807 + 1 não cabe em 64 bits"
+    );
+
+    let min = "int min = -9223372036854775807 - 1;";
+    let error = run_program(&format!("int main() {{ {} return min / -1; }}", min)).unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(RuntimeError::IntegerOverflow(_, _))));
+
+    let error = run_program(&format!("int main() {{ {} return -min; }}", min)).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Erro: Estouro de inteiro: o resultado de -(-9223372036854775808) não cabe em 64 bits"
+    );
+}
+
+#[test]
+fn test_modos_de_estouro() {
+  //no modo wrap o valor dá a volta como no complemento de dois, e no saturate fica no limite
+    let input = r#"
+        int main() {
+            int max = 9223372036854775807;
+            int min = -max - 1;
+            println(max + 1, min - 1, max * 2, min / -1, -min, min % -1);
+            return 0;
+        }
+    "#;
+    let run = |mode| {
+        let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let output = SharedOutput::new();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_overflow_mode(mode);
+        interpreter.interpret(program).unwrap();
+        output.contents()
+    };
+
+    assert_eq!(
+        run(OverflowMode::Wrap),
+        "-9223372036854775808 9223372036854775807 -2 -9223372036854775808 -9223372036854775808 0\n"
+    );
+    assert_eq!(
+        run(OverflowMode::Saturate),
+        "9223372036854775807 -9223372036854775808 9223372036854775807 9223372036854775807 9223372036854775807 0\n"
+    );
+}
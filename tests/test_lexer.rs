@@ -1,76 +1,235 @@
-use mini_interpretador::{Lexer, Token};
+use mini_interpretador::{Lexer, Token, Span, LexErrorKind};
 
 #[test]
 fn test_tokenizar_tokens_basicos() {
 //testa números e operadores básicos
     let mut lexer = Lexer::nova_instancia("123 + 456");
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     
     assert_eq!(tokens.len(), 3);
-    assert_eq!(tokens[0], Token::Number(123));
-    assert_eq!(tokens[1], Token::Mais);
-    assert_eq!(tokens[2], Token::Number(456));
+    assert_eq!(tokens[0].token, Token::Number(123));
+    assert_eq!(tokens[1].token, Token::Mais);
+    assert_eq!(tokens[2].token, Token::Number(456));
 }
 
 #[test]
 fn test_tokenizar_palavras_chave() {
 //teste de palavras-chave
     let mut lexer = Lexer::nova_instancia("int bool return if else while");
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     
-    assert_eq!(tokens[0], Token::Int);
-    assert_eq!(tokens[1], Token::Bool);
-    assert_eq!(tokens[2], Token::Return);
-    assert_eq!(tokens[3], Token::If);
-    assert_eq!(tokens[4], Token::Else);
-    assert_eq!(tokens[5], Token::While);
+    assert_eq!(tokens[0].token, Token::Int);
+    assert_eq!(tokens[1].token, Token::Bool);
+    assert_eq!(tokens[2].token, Token::Return);
+    assert_eq!(tokens[3].token, Token::If);
+    assert_eq!(tokens[4].token, Token::Else);
+    assert_eq!(tokens[5].token, Token::While);
 }
 
 #[test]
 fn test_tokenizar_identificadores() {
 //testa nomes de variáveis e funções
     let mut lexer = Lexer::nova_instancia("main soma variavel_teste");
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     
-    assert_eq!(tokens[0], Token::Ident("main".to_string()));
-    assert_eq!(tokens[1], Token::Ident("soma".to_string()));
-    assert_eq!(tokens[2], Token::Ident("variavel_teste".to_string()));
+    assert_eq!(tokens[0].token, Token::Ident("main".to_string()));
+    assert_eq!(tokens[1].token, Token::Ident("soma".to_string()));
+    assert_eq!(tokens[2].token, Token::Ident("variavel_teste".to_string()));
 }
 
 #[test]
 fn test_tokenizar_operadores() {
 //faz um teste de operadores lógicos e de comparação
     let mut lexer = Lexer::nova_instancia("== != <= >= && ||");
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     
-    assert_eq!(tokens[0], Token::Equal);
-    assert_eq!(tokens[1], Token::NotEqual);
-    assert_eq!(tokens[2], Token::LessEqual);
-    assert_eq!(tokens[3], Token::GreaterEqual);
-    assert_eq!(tokens[4], Token::And);
-    assert_eq!(tokens[5], Token::Or);
+    assert_eq!(tokens[0].token, Token::Equal);
+    assert_eq!(tokens[1].token, Token::NotEqual);
+    assert_eq!(tokens[2].token, Token::LessEqual);
+    assert_eq!(tokens[3].token, Token::GreaterEqual);
+    assert_eq!(tokens[4].token, Token::And);
+    assert_eq!(tokens[5].token, Token::Or);
 }
 
 #[test]
 fn test_tokenizar_pontuacao() {
 //testa os símbolos de pontuações, sinais gráficos e afins
     let mut lexer = Lexer::nova_instancia("( ) { } ; ,");
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     
-    assert_eq!(tokens[0], Token::AbrePar);
-    assert_eq!(tokens[1], Token::FechaPar);
-    assert_eq!(tokens[2], Token::AbreChave);
-    assert_eq!(tokens[3], Token::FechaChave);
-    assert_eq!(tokens[4], Token::PontoEVirgula);
-    assert_eq!(tokens[5], Token::Virgula);
+    assert_eq!(tokens[0].token, Token::AbrePar);
+    assert_eq!(tokens[1].token, Token::FechaPar);
+    assert_eq!(tokens[2].token, Token::AbreChave);
+    assert_eq!(tokens[3].token, Token::FechaChave);
+    assert_eq!(tokens[4].token, Token::PontoEVirgula);
+    assert_eq!(tokens[5].token, Token::Virgula);
 }
 
 #[test]
 fn test_tokenizar_booleanos() {
 //testa valores booleanos
     let mut lexer = Lexer::nova_instancia("true false");
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     
-    assert_eq!(tokens[0], Token::True);
-    assert_eq!(tokens[1], Token::False);
-}
\ No newline at end of file
+    assert_eq!(tokens[0].token, Token::True);
+    assert_eq!(tokens[1].token, Token::False);
+}
+#[test]
+fn test_tokenizar_atribuicoes_compostas() {
+//testa atribuições compostas e incremento/decremento
+    let mut lexer = Lexer::nova_instancia("+= -= *= /= %= ++ -- + -");
+    let tokens = lexer.tokenizador().unwrap();
+
+    assert_eq!(tokens[0].token, Token::MaisIgual);
+    assert_eq!(tokens[1].token, Token::MenosIgual);
+    assert_eq!(tokens[2].token, Token::MultiplicaIgual);
+    assert_eq!(tokens[3].token, Token::DivideIgual);
+    assert_eq!(tokens[4].token, Token::RestoIgual);
+    assert_eq!(tokens[5].token, Token::Incremento);
+    assert_eq!(tokens[6].token, Token::Decremento);
+    assert_eq!(tokens[7].token, Token::Mais);
+    assert_eq!(tokens[8].token, Token::Menos);
+}
+
+#[test]
+fn test_posicao_dos_tokens() {
+//cada token guarda deslocamento em bytes, linha e coluna, mesmo depois de comentários
+    let mut lexer = Lexer::nova_instancia("int x;\n  // comentário\n  x += 10;");
+    let tokens = lexer.tokenizador().unwrap();
+
+    assert_eq!(tokens[0].span, Span::new(0, 3, 1, 1));
+    assert_eq!(tokens[1].span, Span::new(4, 5, 1, 5));
+    assert_eq!(tokens[3].token, Token::Ident("x".to_string()));
+    assert_eq!(tokens[3].span.line, 3);
+    assert_eq!(tokens[3].span.column, 3);
+    assert_eq!(tokens[5].token, Token::Number(10));
+    assert_eq!(tokens[5].span.column, 8);
+    assert_eq!(tokens[5].span.end - tokens[5].span.start, 2);
+}
+
+#[test]
+fn test_erro_caractere_inesperado() {
+//caracteres desconhecidos viram erros, todos reportados de uma vez
+    let mut lexer = Lexer::nova_instancia("x = 3 # 4;\na @ b");
+    let errors = lexer.tokenizador().unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind, LexErrorKind::UnexpectedChar('#'));
+    assert_eq!(errors[0].span, Span::new(6, 7, 1, 7));
+    assert_eq!(errors[1].kind, LexErrorKind::UnexpectedChar('@'));
+    assert_eq!((errors[1].span.line, errors[1].span.column), (2, 3));
+}
+
+#[test]
+fn test_erro_comentario_nao_fechado() {
+//um comentário de bloco sem '*/' é erro, e não pânico
+    let mut lexer = Lexer::nova_instancia("int x; /* sem fim");
+    let errors = lexer.tokenizador().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, LexErrorKind::UnterminatedComment);
+    assert_eq!(errors[0].span.start, 7);
+    assert_eq!(errors[0].to_string(), "Erro léxico: comentário de bloco não fechado");
+}
+
+#[test]
+fn test_erro_literal_inteiro_grande_demais() {
+//literais maiores que i64::MAX são rejeitados em vez de estourar
+    let mut lexer = Lexer::nova_instancia("9223372036854775807 9223372036854775808");
+    let errors = lexer.tokenizador().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, LexErrorKind::IntegerOverflow("9223372036854775808".to_string()));
+    assert_eq!(errors[0].span.start, 20);
+}
+
+#[test]
+fn test_texto_entre_aspas() {
+//o texto vira um único token, e aspas sem fechamento na mesma linha são erro
+    let mut lexer = Lexer::nova_instancia(r#"printf("x = %d", x);"#);
+    let tokens = lexer.tokenizador().unwrap();
+    assert_eq!(tokens[2].token, Token::Str("x = %d".to_string()));
+    assert_eq!(tokens[2].span, Span::new(7, 15, 1, 8));
+
+    let mut lexer = Lexer::nova_instancia("\"sem fim\nint x;");
+    let errors = lexer.tokenizador().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, LexErrorKind::UnterminatedString);
+}
+
+#[test]
+fn test_sequencias_de_escape() {
+//as sequências de escape viram os caracteres que representam; as desconhecidas são erro
+    let mut lexer = Lexer::nova_instancia(r#"string s = "a\tb\n\"c\" \\ \u{e9}";"#);
+    let tokens = lexer.tokenizador().unwrap();
+    assert_eq!(tokens[0].token, Token::String);
+    assert_eq!(tokens[3].token, Token::Str("a\tb\n\"c\" \\ é".to_string()));
+
+    let mut lexer = Lexer::nova_instancia(r#""x\q" "\u{110000}" 1"#);
+    let errors = lexer.tokenizador().unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].kind, LexErrorKind::InvalidEscape("\\q".to_string()));
+    assert_eq!(errors[0].span.end, 5);
+    assert_eq!(errors[1].kind, LexErrorKind::InvalidEscape("\\u{110000}".to_string()));
+}
+
+#[test]
+fn test_literais_decimais() {
+//parte decimal e expoente fazem um double; '.' ou 'e' sem dígitos depois não fazem parte do número
+    let mut lexer = Lexer::nova_instancia("1.5e-3 2.25 3E2 7 4e x");
+    let tokens = lexer.tokenizador().unwrap();
+    let kinds: Vec<Token> = tokens.into_iter().map(|t| t.token).collect();
+
+    assert_eq!(kinds, vec![
+        Token::Decimal(0.0015),
+        Token::Decimal(2.25),
+        Token::Decimal(300.0),
+        Token::Number(7),
+        Token::Number(4),
+        Token::Ident("e".to_string()),
+        Token::Ident("x".to_string()),
+    ]);
+}
+
+#[test]
+fn test_literais_de_caractere() {
+//um caractere ou sequência de escape entre aspas simples; vazio ou com mais de um é erro
+    let mut lexer = Lexer::nova_instancia(r"char c = 'a'; '\n' '\''");
+    let tokens = lexer.tokenizador().unwrap();
+    assert_eq!(tokens[0].token, Token::Char);
+    assert_eq!(tokens[3].token, Token::Character('a'));
+    assert_eq!(tokens[3].span, Span::new(9, 12, 1, 10));
+    assert_eq!(tokens[5].token, Token::Character('\n'));
+    assert_eq!(tokens[6].token, Token::Character('\''));
+
+    let mut lexer = Lexer::nova_instancia("'ab' '' 'x");
+    let errors = lexer.tokenizador().unwrap_err();
+    let kinds: Vec<_> = errors.iter().map(|e| e.kind.clone()).collect();
+    assert_eq!(kinds, vec![LexErrorKind::InvalidCharLiteral; 3]);
+    assert_eq!((errors[0].span.start, errors[0].span.end), (0, 4));
+}
+
+#[test]
+fn test_tokenizar_operadores_bit_a_bit() {
+//'&' e '|' sozinhos, '%', '^', '~' e os deslocamentos, sem confundir com &&, ||, <= e >=
+    let mut lexer = Lexer::nova_instancia("a % b & c && d | e || ~f ^ g << 1 >> 2 <= >=");
+    let tokens: Vec<Token> = lexer.tokenizador().unwrap().into_iter().map(|t| t.token).collect();
+
+    let operators: Vec<&Token> = tokens.iter().filter(|t| !matches!(t, Token::Ident(_) | Token::Number(_))).collect();
+    assert_eq!(
+        operators,
+        vec![
+            &Token::Resto,
+            &Token::BitAnd,
+            &Token::And,
+            &Token::BitOr,
+            &Token::Or,
+            &Token::BitNot,
+            &Token::BitXor,
+            &Token::ShiftLeft,
+            &Token::ShiftRight,
+            &Token::LessEqual,
+            &Token::GreaterEqual,
+        ]
+    );
+}
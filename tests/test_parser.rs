@@ -1,11 +1,11 @@
-use mini_interpretador::{Lexer, Parser, Stmt, Function};
+use mini_interpretador::{Lexer, Parser, Program, Item, Stmt, StmtKind, Expr, ExprKind, BinOp, Initializer, Token, Type};
 
 //função auxiliar para extrair statements onde quer que estejam
-fn get_statements<'a>(functions: &'a [Function], main_body: &'a [Stmt]) -> &'a [Stmt] {
-    if !main_body.is_empty() {
-        main_body
-    } else if !functions.is_empty() {
-        &functions[0].body
+fn get_statements(program: &Program) -> &[Stmt] {
+    if let Some(main) = program.function("main") {
+        &main.body
+    } else if let Some(func) = program.functions().next() {
+        &func.body
     } else {
         panic!("Sem statements encontrados na saída do parser")
     }
@@ -16,26 +16,21 @@ fn test_debug_saida_parser() {
 //mostra o que o parser retorna
     let input = "int main() { return 42; }";
     let mut lexer = Lexer::nova_instancia(input);
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     println!("Tokens: {:?}", tokens);
     
     let mut parser = Parser::new(tokens);
-    let (functions, main_body) = parser.parse();
+    let program = parser.parse().unwrap();
+    let functions: Vec<_> = program.functions().collect();
     
     println!("Contagem de funções: {}", functions.len());
-    println!("Contagem do corpo da função: {}", main_body.len());
     
     if !functions.is_empty() {
         println!("Primeira função: {:?}", functions[0]);
         println!("Tamanho do corpo da função: {}", functions[0].body.len());
     }
     
-    if !main_body.is_empty() {
-        println!("Corpo principal: {:?}", main_body);
-    }
-    
     //este teste sempre passa, serve só para debug
-    assert!(true);
 }
 
 #[test]
@@ -43,16 +38,16 @@ fn test_parse_return_simples() {
 //testa se o parser reconhece o return
     let input = "int main() { return 42; }";
     let mut lexer = Lexer::nova_instancia(input);
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     let mut parser = Parser::new(tokens);
-    let (functions, main_body) = parser.parse();
+    let program = parser.parse().unwrap();
     
-    let statements = get_statements(&functions, &main_body);
+    let statements = get_statements(&program);
     assert!(!statements.is_empty(), "É esperado ao menos um statement");
     
     //deve ter pelo menos um statement (return)
-    match &statements[statements.len() - 1] {
-        Stmt::Return(_) => {}, 
+    match &statements[statements.len() - 1].kind {
+        StmtKind::Return(_) => {}, 
         other => panic!("Era esperado um return, mas obtivemos: {:?}", other),
     }
 }
@@ -62,22 +57,22 @@ fn test_parse_variavel_e_return() {
 //verifica declaração de variável + return
     let input = "int main() { int x = 42; return x; }";
     let mut lexer = Lexer::nova_instancia(input);
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     let mut parser = Parser::new(tokens);
-    let (functions, main_body) = parser.parse();
+    let program = parser.parse().unwrap();
     
-    let statements = get_statements(&functions, &main_body);
+    let statements = get_statements(&program);
     assert!(statements.len() >= 2, "Era esperado ao menos 2 statements (decl de variável + return)");
     
     //procura por declaração de variável
     let has_var_decl = statements.iter().any(|stmt| {
-        matches!(stmt, Stmt::VarDecl { name, .. } if name == "x")
+        matches!(&stmt.kind, StmtKind::VarDecl { name, .. } if name == "x")
     });
     assert!(has_var_decl, "Era esperada declaração de variável pra 'x'");
     
     //procura por return
     let has_return = statements.iter().any(|stmt| {
-        matches!(stmt, Stmt::Return(_))
+        matches!(stmt.kind, StmtKind::Return(_))
     });
     assert!(has_return, "Era esperado um return");
 }
@@ -87,14 +82,16 @@ fn test_parse_funcao_com_params() {
 //verifica se o parser consegue extrair nome, parâmetros e o corpo da função
     let input = "int soma(int a, int b) { return a + b; }";
     let mut lexer = Lexer::nova_instancia(input);
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     let mut parser = Parser::new(tokens);
-    let (functions, _) = parser.parse();
+    let program = parser.parse().unwrap();
+    let functions: Vec<_> = program.functions().collect();
     
     assert_eq!(functions.len(), 1);
     let func = &functions[0];
     assert_eq!(func.name, "soma");
-    assert_eq!(func.params, vec!["a", "b"]);
+    let params: Vec<&str> = func.params.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(params, vec!["a", "b"]);
     
     //a função deve ter pelo menos um statement (return)
     assert!(!func.body.is_empty(), "Corpo da função não devia ser vazio");
@@ -105,15 +102,15 @@ fn test_parse_aritmetica_basica() {
 //verificar se o parser consegue construir expressões aritméticas básicas
     let input = "int main() { return 2 + 3; }";
     let mut lexer = Lexer::nova_instancia(input);
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     let mut parser = Parser::new(tokens);
-    let (functions, main_body) = parser.parse();
+    let program = parser.parse().unwrap();
     
-    let statements = get_statements(&functions, &main_body);
+    let statements = get_statements(&program);
     assert!(!statements.is_empty(), "Esperava-se ao menos 1 statement");
     
     //deve ter um return com expressão aritmética
-    if let Stmt::Return(_expr) = &statements[0] {
+    if let StmtKind::Return(_expr) = &statements[0].kind {
         //temos um return com expressão
     } else {
         panic!("Esperava-se um return com uma expressão aritmética junto");
@@ -128,18 +125,467 @@ fn test_parse_multiplas_funcoes() {
         int main() { return soma(1, 2); }
     "#;
     let mut lexer = Lexer::nova_instancia(input);
-    let tokens = lexer.tokenizador();
+    let tokens = lexer.tokenizador().unwrap();
     let mut parser = Parser::new(tokens);
-    let (functions, _main_body) = parser.parse();
+    let program = parser.parse().unwrap();
     
     //deve ter pelo menos 1 função (soma)
-    assert!(!functions.is_empty(), "Esperava-se ao menos 1 função");
+    assert!(program.functions().next().is_some(), "Esperava-se ao menos 1 função");
     
     //verifica se temos a função soma
-    let soma_func = functions.iter().find(|f| f.name == "soma");
+    let soma_func = program.function("soma");
     assert!(soma_func.is_some(), "Era esperada a função 'soma'");
     
     let soma = soma_func.unwrap();
     assert_eq!(soma.params.len(), 2, "soma só devia ter 2 parâMetros");
     assert!(!soma.body.is_empty(), "o corpo de soma não devia estar vazio");
-}
\ No newline at end of file
+}
+#[test]
+fn test_parse_atribuicao() {
+//verifica se a atribuição vira um Expr::Assign associativo à direita
+    let input = "int main() { int a = 0; int b = 0; a = b = 1; }";
+    let mut lexer = Lexer::nova_instancia(input);
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+
+    let statements = get_statements(&program);
+    match &statements[2].kind {
+        StmtKind::ExprStmt(expr) => {
+            let ExprKind::Assign { target, value } = &expr.kind else {
+                panic!("Era esperada uma atribuição, mas obtivemos: {:?}", expr);
+            };
+            assert!(matches!(&target.kind, ExprKind::Var(name) if name == "a"));
+            let ExprKind::Assign { target, .. } = &value.kind else {
+                panic!("Era esperada uma atribuição, mas obtivemos: {:?}", value);
+            };
+            assert!(matches!(&target.kind, ExprKind::Var(name) if name == "b"));
+        }
+        other => panic!("Era esperada uma atribuição, mas obtivemos: {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_variavel_global() {
+//variáveis no escopo do arquivo viram itens globais, na ordem em que aparecem
+    let input = "int contador = 0; int main() { return contador; } bool ativo = true;";
+    let mut lexer = Lexer::nova_instancia(input);
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+
+    assert_eq!(program.items.len(), 3);
+    assert!(matches!(&program.items[0], Item::Global(Stmt { kind: StmtKind::VarDecl { name, .. }, .. }) if name == "contador"));
+    assert!(matches!(&program.items[1], Item::Function(f) if f.name == "main"));
+    assert!(matches!(&program.items[2], Item::Global(Stmt { kind: StmtKind::VarDecl { name, .. }, .. }) if name == "ativo"));
+}
+
+#[test]
+fn test_parse_varios_erros_numa_passada() {
+//o parser se recupera no ';' e reporta todos os erros, sem entrar em pânico
+    let input = r#"
+        int main() {
+            int x = ;
+            int y = 2
+            return x + y;
+        }
+    "#;
+    let mut lexer = Lexer::nova_instancia(input);
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let errors = parser.parse().unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].expected, "expressão");
+    assert_eq!(errors[0].found, Some(Token::PontoEVirgula));
+    assert_eq!(errors[1].expected, "';'");
+    assert_eq!(errors[1].found, Some(Token::Return));
+}
+
+#[test]
+fn test_parse_erro_no_fim_do_arquivo() {
+//um programa cortado no meio deve virar erro, e não pânico
+    let mut lexer = Lexer::nova_instancia("int main() { return");
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let errors = parser.parse().unwrap_err();
+
+    assert!(!errors.is_empty());
+    assert_eq!(errors[0].found, None);
+    assert_eq!(errors[0].to_string(), "Erro de sintaxe: esperado expressão, mas o arquivo acabou");
+}
+
+#[test]
+fn test_parse_erro_entre_funcoes() {
+//um erro numa função não impede o parse das outras
+    let input = r#"
+        int quebrada(int a b) { return a; }
+        int main() { return 1 +; }
+        int ok() { return 2; }
+    "#;
+    let mut lexer = Lexer::nova_instancia(input);
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let errors = parser.parse().unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].expected, "')'");
+    assert_eq!(errors[0].found, Some(Token::Ident("b".to_string())));
+    assert_eq!(errors[1].expected, "expressão");
+}
+
+#[test]
+fn test_parse_erro_no_cabecalho_do_if() {
+//o bloco de um if com erro no cabeçalho é pulado inteiro, sem gerar erros falsos fora da função
+    let input = "int main() { if (x > ) { return 1; } else { return 2; } return 0; }";
+    let mut lexer = Lexer::nova_instancia(input);
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let errors = parser.parse().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].expected, "expressão");
+    assert_eq!(errors[0].found, Some(Token::FechaPar));
+}
+
+#[test]
+fn test_parse_spans_dos_nos() {
+//statements e expressões carregam o trecho do código de onde vieram
+    let input = "int main() {\n    return 1 + soma(2, 3);\n}";
+    let mut lexer = Lexer::nova_instancia(input);
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let program = parser.parse().unwrap();
+
+    let statements = get_statements(&program);
+    let stmt = &statements[0];
+    assert_eq!((stmt.span.line, stmt.span.column), (2, 5));
+    assert_eq!(&input[stmt.span.start..stmt.span.end], "return 1 + soma(2, 3);");
+
+    let StmtKind::Return(expr) = &stmt.kind else {
+        panic!("Era esperado um return");
+    };
+    assert_eq!(&input[expr.span.start..expr.span.end], "1 + soma(2, 3)");
+}
+
+#[test]
+fn test_parse_erro_com_posicao() {
+//o erro de sintaxe aponta a linha e a coluna do token problemático
+    let input = "int main() {\n    int x = 1\n    return x;\n}";
+    let mut lexer = Lexer::nova_instancia(input);
+    let tokens = lexer.tokenizador().unwrap();
+    let mut parser = Parser::new(tokens);
+    let errors = parser.parse().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!((errors[0].span.line, errors[0].span.column), (3, 5));
+}
+
+#[test]
+fn test_parse_tipos_e_conversao() {
+//os tipos declarados ficam na AST, float é sinônimo de double e (int)x é uma conversão
+    let input = "double media(int a, float b) { double m = (a + b) / 2; return (int)m; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    let func = program.function("media").unwrap();
+
+    assert_eq!(func.return_type, Type::Double);
+    assert_eq!(func.params[0].ty, Type::Int);
+    assert_eq!(func.params[1].ty, Type::Double);
+    assert!(matches!(&func.body[0].kind, StmtKind::VarDecl { ty: Type::Double, .. }));
+
+    let StmtKind::Return(expr) = &func.body[1].kind else {
+        panic!("Era esperado um return");
+    };
+    assert!(matches!(&expr.kind, ExprKind::Cast { ty: Type::Int, .. }));
+    assert_eq!(&input[expr.span.start..expr.span.end], "(int)m");
+}
+
+#[test]
+fn test_parse_arrays() {
+//declaração com tamanho ou inicializador, indexação como alvo de atribuição e parâMetro array
+    let input = "int soma(int v[], int n) { int w[] = {1, 2}; v[n - 1] = w[0]; return 0; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    let func = program.function("soma").unwrap();
+
+    assert!(func.params[0].is_array);
+    assert!(!func.params[1].is_array);
+    assert!(matches!(
+        &func.body[0].kind,
+        StmtKind::ArrayDecl { dims, init: Some(Initializer::List(values, _)), .. }
+            if dims.len() == 1 && dims[0].is_none() && values.len() == 2
+    ));
+
+    let StmtKind::ExprStmt(expr) = &func.body[1].kind else {
+        panic!("Era esperada uma atribuição");
+    };
+    let ExprKind::Assign { target, value } = &expr.kind else {
+        panic!("Era esperada uma atribuição");
+    };
+    assert!(matches!(&target.kind, ExprKind::Index { .. }));
+    assert_eq!(&input[value.span.start..value.span.end], "w[0]");
+}
+
+#[test]
+fn test_parse_array_sem_tamanho_nem_valores() {
+//int v[]; não diz quantos elementos o array tem
+    let tokens = Lexer::nova_instancia("int main() { int v[]; return 0; }").tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].found, Some(Token::PontoEVirgula));
+}
+
+#[test]
+fn test_parse_array_multidimensional() {
+//cada dimensão tem o seu tamanho, o inicializador pode ter listas dentro de listas e só a primeira
+//dimensão pode ficar sem tamanho
+    let input = "int main() { int m[][2] = {{1, 2}, {3}}; m[1][1] = m[0][1]; return 0; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let statements = get_statements(&program);
+    let StmtKind::ArrayDecl { dims, init: Some(Initializer::List(rows, _)), .. } = &statements[0].kind else {
+        panic!("Era esperada a declaração de um array");
+    };
+    assert_eq!(dims.len(), 2);
+    assert!(dims[0].is_none() && dims[1].is_some());
+    assert!(matches!(&rows[1], Initializer::List(values, span) if values.len() == 1 && &input[span.start..span.end] == "{3}"));
+
+    let tokens = Lexer::nova_instancia("int main() { int m[2][] = {{1}}; return 0; }").tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+    assert_eq!(errors[0].expected, "tamanho da dimensão do array");
+}
+
+#[test]
+fn test_parse_struct() {
+//a definição da struct é um item do arquivo, e p.x é um acesso a campo que pode receber atribuição
+    let input = r#"
+        struct Ponto { int x; double y; };
+        struct Ponto origem() { struct Ponto p; return p; }
+        int main() { struct Ponto p = {1, 2.5}; p.x = 3; return p.x; }
+    "#;
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let Item::Struct(def) = &program.items[0] else {
+        panic!("Era esperada a definição de uma struct");
+    };
+    assert_eq!(def.name, "Ponto");
+    let fields: Vec<(&str, &Type)> = def.fields.iter().map(|f| (f.name.as_str(), &f.ty)).collect();
+    assert_eq!(fields, vec![("x", &Type::Int), ("y", &Type::Double)]);
+
+    let origem = program.function("origem").unwrap();
+    assert_eq!(origem.return_type, Type::Struct("Ponto".to_string()));
+    assert!(matches!(&origem.body[0].kind, StmtKind::VarDecl { init: None, .. }));
+
+    let statements = get_statements(&program);
+    assert!(matches!(
+        &statements[0].kind,
+        StmtKind::VarDecl { ty: Type::Struct(name), init: Some(Initializer::List(values, _)), .. }
+            if name == "Ponto" && values.len() == 2
+    ));
+    let StmtKind::ExprStmt(expr) = &statements[1].kind else {
+        panic!("Era esperada uma atribuição");
+    };
+    let ExprKind::Assign { target, .. } = &expr.kind else {
+        panic!("Era esperada uma atribuição");
+    };
+    assert!(matches!(&target.kind, ExprKind::Field { field, .. } if field == "x"));
+}
+
+#[test]
+fn test_parse_struct_com_campo_repetido() {
+//dois campos com o mesmo nome na mesma struct são um erro de sintaxe
+    let tokens = Lexer::nova_instancia("struct P { int x; int x; };").tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+
+    assert_eq!(errors[0].expected, "um nome de campo que ainda não existe em 'P'");
+    assert_eq!(errors[0].found, Some(Token::Ident("x".to_string())));
+}
+
+#[test]
+fn test_parse_atribuicao_a_campo_de_valor_temporario() {
+//o campo de um valor que não está guardado em lugar nenhum não pode receber atribuição
+    for input in [
+        "int main() { f().x = 3; return 0; }",
+        "int main() { \"abc\".x = 1; return 0; }",
+        "int main() { (c ? p : q).x++; return 0; }",
+    ] {
+        let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+        let errors = Parser::new(tokens).parse().unwrap_err();
+        assert_eq!(errors.len(), 1, "{}", input);
+    }
+
+    let tokens = Lexer::nova_instancia("int main() { v[0].x = 1; p.a.b += 2; return 0; }").tokenizador().unwrap();
+    assert!(Parser::new(tokens).parse().is_ok());
+}
+
+#[test]
+fn test_parse_enum() {
+//cada constante vale a anterior mais um, a não ser que tenha um valor constante depois do '='
+    let input = "enum Cor { VERMELHO, VERDE = 5, AZUL, ROXO = AZUL * 2 - 'a' + 97, }; enum Cor c = AZUL;";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let Item::Enum(def) = &program.items[0] else {
+        panic!("Era esperada a definição de um enum");
+    };
+    let constants: Vec<(&str, i64)> = def.constants.iter().map(|(name, value)| (name.as_str(), *value)).collect();
+    assert_eq!(constants, vec![("VERMELHO", 0), ("VERDE", 5), ("AZUL", 6), ("ROXO", 12)]);
+    assert!(matches!(&program.items[1], Item::Global(stmt) if matches!(stmt.kind, StmtKind::VarDecl { ty: Type::Int, .. })));
+
+    let tokens = Lexer::nova_instancia("int n = 1; enum E { A = n, B };").tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+    assert_eq!(errors[0].expected, "um valor inteiro constante");
+}
+
+#[test]
+fn test_parse_enum_estouro_do_proximo_valor() {
+//depois do maior int, a próxima constante precisa de um valor escrito, em vez de dar a volta
+    let input = "enum E { A = 9223372036854775807, B };";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].expected, "um valor para a constante, já que a anterior é o maior int possível");
+    assert_eq!(errors[0].found, Some(Token::Ident("B".to_string())));
+
+    let input = "enum E { A = 9223372036854775807, B = 0 };";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    assert!(Parser::new(tokens).parse().is_ok());
+}
+
+#[test]
+fn test_parse_switch() {
+//os valores dos cases são calculados pelo parser, e um valor repetido é um erro de sintaxe
+    let input = "enum C { A = 2 }; int main() { switch (x) { case 1: case A * 2: x = 0; break; default: x = 1; } return 0; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let statements = get_statements(&program);
+    let StmtKind::Switch { cases, .. } = &statements[0].kind else {
+        panic!("Era esperado um switch");
+    };
+    let labels: Vec<Option<i64>> = cases.iter().map(|case| case.label).collect();
+    assert_eq!(labels, vec![Some(1), Some(4), None]);
+    assert!(cases[0].body.is_empty());
+    assert!(matches!(cases[1].body[1].kind, StmtKind::Break));
+
+    let input = "int main() { switch (x) { case 'a': break; case 97: break; } return 0; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+    assert_eq!(errors[0].expected, "um valor que ainda não apareceu em outro 'case'");
+    assert_eq!(errors[0].found, Some(Token::Number(97)));
+}
+
+#[test]
+fn test_parse_switch_com_case_invalido() {
+//um case com valor inválido é registrado e o resto do switch continua sendo analisado
+    let input = "int main() { switch (x) { case B: x = 2; break; case 1: x = 3 break; default: x = 4; } return 0; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].expected, "um valor inteiro constante no 'case'");
+    assert_eq!(errors[0].found, Some(Token::Ident("B".to_string())));
+    assert_eq!(errors[1].expected, "';'");
+    assert_eq!(errors[1].found, Some(Token::Break));
+}
+
+#[test]
+fn test_parse_break_fora_do_switch() {
+//um break fora de um switch é rejeitado pelo parser
+    let tokens = Lexer::nova_instancia("int main() { break; return 0; }").tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].found, Some(Token::Break));
+}
+
+#[test]
+fn test_parse_continue_fora_do_laco() {
+//continue só vale dentro de um laço, mesmo que esteja dentro de um switch
+    let input = "int main() { switch (1) { default: continue; } while (1) { switch (2) { default: continue; } } return 0; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].found, Some(Token::Continue));
+    assert_eq!(errors[0].expected, "statement ('continue' só pode ser usado dentro de um laço)");
+}
+
+#[test]
+fn test_parse_do_while() {
+//o do-while tem o corpo antes da condição e termina com ';'
+    let input = "int main() { int i = 0; do { i++; } while (i < 3); return i; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let statements = get_statements(&program);
+    assert!(matches!(&statements[1].kind, StmtKind::DoWhile { body, .. } if body.len() == 1));
+    assert_eq!(&input[statements[1].span.start..statements[1].span.end], "do { i++; } while (i < 3);");
+
+    let tokens = Lexer::nova_instancia("int main() { do { } while (1) return 0; }").tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+    assert_eq!(errors[0].expected, "';'");
+}
+
+#[test]
+fn test_parse_precedencia_bit_a_bit() {
+//como em C: | < ^ < & < igualdade, e os deslocamentos ficam entre a comparação e a adição
+    fn binary(expr: &Expr) -> (BinOp, &Expr, &Expr) {
+        match &expr.kind {
+            ExprKind::Binary { op, lhs, rhs } => (*op, lhs, rhs),
+            other => panic!("Era esperada uma expressão binária, mas veio {:?}", other),
+        }
+    }
+
+    let input = "int main() { return a | b ^ c & d == e; return 1 << 2 + 3 < x % 4; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    let statements = get_statements(&program);
+
+    let StmtKind::Return(expr) = &statements[0].kind else { panic!("Era esperado um return") };
+    let (op, _, rhs) = binary(expr);
+    assert!(matches!(op, BinOp::BitOr));
+    let (op, _, rhs) = binary(rhs);
+    assert!(matches!(op, BinOp::BitXor));
+    let (op, _, rhs) = binary(rhs);
+    assert!(matches!(op, BinOp::BitAnd));
+    assert!(matches!(binary(rhs).0, BinOp::Equal));
+
+    let StmtKind::Return(expr) = &statements[1].kind else { panic!("Era esperado um return") };
+    let (op, lhs, rhs) = binary(expr);
+    assert!(matches!(op, BinOp::Less));
+    assert!(matches!(binary(rhs).0, BinOp::Mod));
+    let (op, _, shifted) = binary(lhs);
+    assert!(matches!(op, BinOp::ShiftLeft));
+    assert!(matches!(binary(shifted).0, BinOp::Add));
+}
+
+#[test]
+fn test_parse_condicional() {
+//o ?: fica abaixo do || e acima da atribuição, e é associativo à direita
+    let input = "int main() { x = a || b ? 1 : c ? 2 : 3; return 0; }";
+    let tokens = Lexer::nova_instancia(input).tokenizador().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+
+    let StmtKind::ExprStmt(expr) = &get_statements(&program)[0].kind else {
+        panic!("Era esperada uma expressão");
+    };
+    let ExprKind::Assign { value, .. } = &expr.kind else {
+        panic!("Era esperada uma atribuição");
+    };
+    let ExprKind::Conditional { condition, else_expr, .. } = &value.kind else {
+        panic!("Era esperado um condicional");
+    };
+    assert!(matches!(condition.kind, ExprKind::Logical { .. }));
+    assert!(matches!(else_expr.kind, ExprKind::Conditional { .. }));
+    assert_eq!(&input[value.span.start..value.span.end], "a || b ? 1 : c ? 2 : 3");
+
+    let tokens = Lexer::nova_instancia("int main() { return a ? 1; }").tokenizador().unwrap();
+    let errors = Parser::new(tokens).parse().unwrap_err();
+    assert_eq!(errors[0].expected, "':' do operador condicional");
+}
@@ -0,0 +1,129 @@
+use mini_interpretador::Repl;
+
+//alimenta o REPL com várias linhas e devolve tudo o que ele escreveu
+fn rodar_repl(repl: &mut Repl, linhas: &[&str]) -> String {
+    let mut saida = Vec::new();
+    for linha in linhas {
+        repl.feed_line(linha, &mut saida).unwrap();
+    }
+    String::from_utf8(saida).unwrap()
+}
+
+#[test]
+fn test_repl_estado_persistente() {
+//funções e variáveis continuam valendo entre uma entrada e outra
+    let mut repl = Repl::new();
+    let saida = rodar_repl(&mut repl, &[
+        "int dobro(int n) { return n * 2; }",
+        "int x = 20;",
+        "x += 1;",
+        "dobro(x)",
+    ]);
+
+    assert_eq!(saida, "42\n");
+}
+
+#[test]
+fn test_repl_entrada_em_varias_linhas() {
+//enquanto houver chaves abertas, o REPL espera mais linhas antes de executar
+    let mut repl = Repl::new();
+    assert_eq!(repl.prompt(), "mc> ");
+
+    let saida = rodar_repl(&mut repl, &["int fat(int n) {", "  if (n <= 1) { return 1; }"]);
+    assert_eq!(saida, "");
+    assert_eq!(repl.prompt(), "... ");
+
+    let saida = rodar_repl(&mut repl, &["  return n * fat(n - 1);", "}", "fat(5)"]);
+    assert_eq!(saida, "120\n");
+    assert_eq!(repl.prompt(), "mc> ");
+}
+
+#[test]
+fn test_repl_comandos() {
+//:funcs lista as funções e :reset apaga o estado
+    let mut repl = Repl::new();
+    let saida = rodar_repl(&mut repl, &[
+        "int soma(int a, int b) { return a + b; }",
+        "int x = 1;",
+        ":funcs",
+        ":reset",
+        ":funcs",
+    ]);
+    assert_eq!(saida, "soma(a, b)\nestado do interpretador apagado\n");
+
+    let saida = rodar_repl(&mut repl, &["x"]);
+    assert!(saida.starts_with("erro: Variável 'x' não definida"));
+}
+
+#[test]
+fn test_repl_erro_nao_encerra_sessao() {
+//um erro é mostrado, mas o REPL continua aceitando entradas
+    let mut repl = Repl::new();
+    let saida = rodar_repl(&mut repl, &["1 / 0", "int y = ;", "2 + 3"]);
+
+    assert!(saida.contains("erro: Divisão por zero"));
+    assert!(saida.contains("erro: erro de sintaxe, esperado expressão"));
+    assert!(saida.ends_with("5\n"));
+
+    let mut saida = Vec::new();
+    assert!(!repl.feed_line(":quit", &mut saida).unwrap());
+}
+
+#[test]
+fn test_repl_erro_em_funcao_de_entrada_anterior() {
+//um erro dentro de uma função definida antes é mostrado na linha da definição, não na entrada atual
+    let mut repl = Repl::new();
+    let saida = rodar_repl(&mut repl, &["int f(int x) {", " int y = 1;", " return 10 / x;", "}", "f(0)"]);
+
+    assert!(saida.contains("<repl>:3:9"));
+    assert!(saida.contains("3 |  return 10 / x;"));
+}
+
+#[test]
+fn test_repl_constantes_de_enum_nos_case() {
+//as constantes de um enum definido numa entrada anterior podem ser usadas nos case
+    let mut repl = Repl::new();
+    let saida = rodar_repl(&mut repl, &[
+        "enum Cor { VERMELHO, VERDE };",
+        "int c = 1;",
+        "switch (c) { case VERDE: println(\"verde\"); break; default: println(\"outra\"); }",
+    ]);
+
+    assert_eq!(saida, "verde\n");
+}
+
+#[test]
+fn test_repl_variaveis_visiveis_nas_funcoes() {
+//as variáveis declaradas no REPL são globais, então as funções definidas depois enxergam elas
+    let mut repl = Repl::new();
+    let saida = rodar_repl(&mut repl, &["int contador = 5;", "int f() { return contador; }", "contador += 1;", "f()"]);
+
+    assert_eq!(saida, "6\n");
+}
+
+#[test]
+fn test_repl_load_do_mesmo_arquivo_duas_vezes() {
+//carregar de novo um arquivo editado troca as funções e as variáveis globais dele
+    let caminho = std::env::temp_dir().join(format!("repl_load_{}.mc", std::process::id()));
+    let caminho_texto = caminho.to_str().unwrap().to_string();
+    let comando = format!(":load {}", caminho_texto);
+    let mut repl = Repl::new();
+
+    std::fs::write(&caminho, "int g = 1; int f() { return g; }").unwrap();
+    let saida = rodar_repl(&mut repl, &[&comando, "f()"]);
+    assert!(saida.ends_with("1\n"));
+
+    std::fs::write(&caminho, "int g = 2; int f() { return g * 10; }").unwrap();
+    let saida = rodar_repl(&mut repl, &[&comando, "f()"]);
+    std::fs::remove_file(&caminho).unwrap();
+    assert_eq!(saida, format!("1 função(ões) carregada(s) de '{}'\n20\n", caminho_texto));
+}
+
+#[test]
+fn test_repl_saida_do_programa() {
+//o que o programa imprime vai para a mesma saída do REPL, antes do valor da entrada, e continua assim depois de um :reset
+    let mut repl = Repl::new();
+    let saida = rodar_repl(&mut repl, &["int f() { printf(\"%d!\", 7); return 1; }", "f()", ":reset", "println(\"oi\")"]);
+
+    assert_eq!(saida, "7!1\nestado do interpretador apagado\noi\n");
+}